@@ -0,0 +1,218 @@
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+
+use seq_macro::seq;
+
+use crate::data::{DropGuard, RawDataPtr};
+use crate::slice::*;
+
+macro_rules! declare_array_n {
+    (
+        $array:ident,
+        $slice:ident,
+        $slice_ref:ident,
+        $slice_ref_mut:ident,
+        $n:literal
+    ) => { seq!(I in 0..$n {
+        /// A fixed-capacity vector that stores up to `CAP` rows inline and never allocates.
+        ///
+        /// Every column lives in an inline `[MaybeUninit<T>; CAP]` array, and pushing
+        /// into a full vector hands the row back instead of growing.
+        ///
+        /// The columns move along with the vector, so it doesn't keep any pointers into
+        /// itself. Use [`as_slice`](Self::as_slice) and [`as_mut_slice`](Self::as_mut_slice)
+        /// for the indexing and iteration API of the slice, which are built from the
+        /// inline columns wherever the vector currently is.
+        pub struct $array<const CAP: usize, #(T~I: Sized,)*> {
+            len: usize,
+            columns: UnsafeCell<(#([MaybeUninit<T~I>; CAP],)*)>,
+        }
+
+        impl<const CAP: usize, #(T~I: Sized,)*> $array<CAP, #(T~I,)*> {
+            #[inline]
+            pub fn new() -> Self {
+                Self {
+                    len: 0,
+                    columns: UnsafeCell::new((#([const { MaybeUninit::uninit() }; CAP],)*)),
+                }
+            }
+
+            #[inline(always)]
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// Returns whether the vector holds `CAP` rows and can't accept any more.
+            #[inline(always)]
+            pub fn is_full(&self) -> bool {
+                self.len == CAP
+            }
+
+            #[inline(always)]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            #[inline(always)]
+            pub fn capacity(&self) -> usize {
+                CAP
+            }
+
+            /// Returns the number of rows that can still be pushed.
+            #[inline(always)]
+            pub fn remaining_capacity(&self) -> usize {
+                CAP - self.len
+            }
+
+            /// Forces the length of the vector to `new_len`.
+            ///
+            /// # Safety
+            ///
+            /// It is up to the caller to guarantee the following:
+            /// - `new_len <= CAP`
+            /// - All elements in the range `self.len()..new_len` are valid in every column
+            #[inline(always)]
+            pub unsafe fn set_len(&mut self, new_len: usize) {
+                debug_assert!(new_len <= CAP);
+                self.len = new_len;
+            }
+
+            /// Returns the remaining spare capacity of each column as `MaybeUninit<T>` slices.
+            ///
+            /// Each returned slice covers the range `self.len()..CAP` of its column. Once
+            /// the data has been written, use [`set_len`](Self::set_len) to mark it as
+            /// initialized.
+            #[inline]
+            pub fn spare_capacity_mut(&mut self) -> (#(&mut [MaybeUninit<T~I>],)*) {
+                let len = self.len;
+                let columns = self.columns.get_mut();
+                (#(&mut columns.I[len..],)*)
+            }
+
+            /// Checks that all of this vector's invariants hold.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic with a description of the first violated invariant.
+            #[inline]
+            pub fn validate(&self) {
+                assert!(
+                    self.len <= CAP,
+                    "{}::validate: len {} exceeds capacity {}",
+                    stringify!($array),
+                    self.len,
+                    CAP,
+                );
+            }
+
+            /// Appends a row to the back of the vector.
+            ///
+            /// If the vector is full, the row is handed back as the error.
+            #[inline]
+            pub fn push(&mut self, data: (#(T~I,)*)) -> Result<(), (#(T~I,)*)> {
+                if self.len == CAP {
+                    return Err(data);
+                }
+
+                let len = self.len;
+                let columns = self.columns.get_mut();
+                #(columns.I[len] = MaybeUninit::new(data.I);)*
+
+                self.len += 1;
+                Ok(())
+            }
+
+            #[inline]
+            pub fn clear(&mut self) {
+                let slice = self.raw_slice();
+
+                // Set the length first, so that a panicking destructor can never cause
+                // any element to be observed or dropped again after this call.
+                self.len = 0;
+
+                let _guards = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len,
+                    // and nothing else can access that data now that self.len is 0.
+                    (#(DropGuard::new(&slice.data.I, slice.len),)*)
+                };
+            }
+
+            #[inline]
+            pub fn swap_remove(&mut self, index: usize) -> (#(T~I,)*) {
+                assert!(index < self.len);
+
+                let mut slice = self.raw_slice();
+                self.len -= 1;
+
+                unsafe {
+                    // SAFETY: We guarantee that the data in the range 0..len is valid,
+                    // and we check that the index is within bounds using the assert above.
+                    (#(slice.data.I.swap_remove(index, slice.len),)*)
+                }
+            }
+
+            #[inline]
+            pub fn as_slice(&self) -> $slice_ref<'_, #(T~I,)*> {
+                $slice_ref {
+                    slice: self.raw_slice(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> $slice_ref_mut<'_, #(T~I,)*> {
+                $slice_ref_mut {
+                    slice: self.raw_slice(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            /// Builds a slice over the rows in the inline columns where they are now.
+            ///
+            /// The slice is only valid until the vector is next moved or modified, so
+            /// callers must tie it to a borrow of self.
+            #[inline(always)]
+            fn raw_slice(&self) -> $slice<#(T~I,)*> {
+                let columns = self.columns.get();
+
+                let data = unsafe {
+                    // SAFETY: Taking field addresses doesn't create any references, and
+                    // each inline column holds CAP elements, so these are never null.
+                    (#(
+                        RawDataPtr::from_block(
+                            NonNull::new_unchecked(ptr::addr_of_mut!((*columns).I)).cast(),
+                            CAP,
+                        ),
+                    )*)
+                };
+
+                #(data.I.mark_init(0, self.len);)*
+                $slice { len: self.len, data }
+            }
+        }
+
+        impl<const CAP: usize, #(T~I: Sized,)*> Default for $array<CAP, #(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<const CAP: usize, #(T~I: Sized,)*> Drop for $array<CAP, #(T~I,)*> {
+            #[inline]
+            fn drop(&mut self) {
+                self.clear();
+            }
+        }
+
+        // SAFETY: The columns are only written through a mutable reference, or through
+        // a mutable slice view, which borrows the vector mutably.
+        unsafe impl<const CAP: usize, #(T~I: Sync,)*> Sync for $array<CAP, #(T~I,)*> {}
+    });}
+}
+
+seq!(N in 2..=12 {
+    declare_array_n!(ArrayVecSoa~N, SliceSoa~N, SliceSoaRef~N, SliceSoaRefMut~N, N);
+});
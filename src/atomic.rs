@@ -0,0 +1,93 @@
+//! Atomic views of primitive columns.
+//!
+//! A column of `u32` (or any other [`AtomicElem`]) can be viewed as a slice of
+//! [`AtomicU32`] for as long as the slice is mutably borrowed. The view itself is a
+//! shared slice, so it can be handed to several threads at once, for example to
+//! scatter-add into the column from a parallel loop.
+
+use core::mem::{align_of, size_of};
+use core::sync::atomic::*;
+
+use seq_macro::seq;
+
+use crate::slice::*;
+
+/// Primitive types with an atomic counterpart of the same size.
+///
+/// # Safety
+///
+/// It is up to the implementer to guarantee that `Atomic` has the same size and bit
+/// validity as `Self`, and is safe to access through a shared reference from any
+/// thread. Its alignment is checked when a column is viewed.
+pub unsafe trait AtomicElem: Sized {
+    /// The atomic type, such as [`AtomicU32`] for `u32`.
+    type Atomic: Sync;
+}
+
+macro_rules! impl_atomic_elem {
+    ($($width:literal: $($t:ty => $atomic:ty),*;)*) => {
+        $($(
+            #[cfg(target_has_atomic = $width)]
+            unsafe impl AtomicElem for $t {
+                type Atomic = $atomic;
+            }
+        )*)*
+    };
+}
+
+impl_atomic_elem! {
+    "8": bool => AtomicBool, u8 => AtomicU8, i8 => AtomicI8;
+    "16": u16 => AtomicU16, i16 => AtomicI16;
+    "32": u32 => AtomicU32, i32 => AtomicI32;
+    "64": u64 => AtomicU64, i64 => AtomicI64;
+    "ptr": usize => AtomicUsize, isize => AtomicIsize;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl<T> AtomicElem for *mut T {
+    type Atomic = AtomicPtr<T>;
+}
+
+/// Views a mutably borrowed slice as a slice of atomics.
+///
+/// This fails to compile for types whose atomic counterpart is more strictly
+/// aligned on the target, such as `u64` on some 32-bit platforms.
+#[inline(always)]
+fn as_atomic_slice<T: AtomicElem>(slice: &mut [T]) -> &[T::Atomic] {
+    const {
+        assert!(size_of::<T>() == size_of::<T::Atomic>());
+        assert!(align_of::<T>() == align_of::<T::Atomic>());
+    };
+
+    unsafe {
+        // SAFETY: The atomic type has the same layout as T, and the mutable borrow
+        // guarantees that nothing else accesses the slice non-atomically.
+        &*(slice as *mut [T] as *const [T::Atomic])
+    }
+}
+
+macro_rules! impl_n {
+    ($slice:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I,)*> $slice<#(T~I,)*> {
+            /// Returns the column at position `K` as a slice of atomics, such as
+            /// `&[AtomicU32]` for a column of `u32`.
+            ///
+            /// The column stays mutably borrowed while the returned slice lives, so
+            /// every access in the meantime goes through the atomics.
+            #[inline]
+            pub fn as_atomic_column<const K: usize>(
+                &mut self,
+            ) -> &[<<Self as SoaColumn<K>>::Elem as AtomicElem>::Atomic]
+            where
+                Self: SoaColumn<K>,
+                <Self as SoaColumn<K>>::Elem: AtomicElem,
+            {
+                as_atomic_slice(SoaColumn::<K>::column_at_mut(self))
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(SliceSoa~N, N);
+});
@@ -0,0 +1,265 @@
+//! Runtime-checked column borrows through a shared reference.
+//!
+//! A `CellVecSoaN` wraps a `VecSoaN` and lends out its columns one at a time, like a
+//! [`RefCell`](core::cell::RefCell) per column: any number of shared borrows or a
+//! single mutable borrow of each column can be alive at once, and different columns
+//! can be borrowed independently. This suits callback-driven code where each
+//! callback only knows which columns it needs when it runs.
+
+use core::cell::{Cell, UnsafeCell};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+use allocator_api2::alloc::{Allocator, Global};
+use seq_macro::seq;
+
+use crate::error::BorrowError;
+use crate::slice::*;
+use crate::vec::*;
+
+/// A borrow flag value for a column that is borrowed mutably.
+const WRITING: isize = -1;
+
+/// A shared borrow of a single column, released when dropped.
+pub struct ColumnRef<'a, T> {
+    column: NonNull<[T]>,
+    flag: &'a Cell<isize>,
+    lifetime: PhantomData<&'a [T]>,
+}
+
+impl<T> Deref for ColumnRef<'_, T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        unsafe {
+            // SAFETY: The borrow flag guarantees that nothing writes to the column
+            // while this guard is alive.
+            self.column.as_ref()
+        }
+    }
+}
+
+impl<T> Drop for ColumnRef<'_, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.flag.set(self.flag.get() - 1);
+    }
+}
+
+/// A mutable borrow of a single column, released when dropped.
+pub struct ColumnRefMut<'a, T> {
+    column: NonNull<[T]>,
+    flag: &'a Cell<isize>,
+    lifetime: PhantomData<&'a mut [T]>,
+}
+
+impl<T> Deref for ColumnRefMut<'_, T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        unsafe {
+            // SAFETY: The borrow flag guarantees that this guard is the only access
+            // to the column while it is alive.
+            self.column.as_ref()
+        }
+    }
+}
+
+impl<T> DerefMut for ColumnRefMut<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe {
+            // SAFETY: The borrow flag guarantees that this guard is the only access
+            // to the column while it is alive.
+            self.column.as_mut()
+        }
+    }
+}
+
+impl<T> Drop for ColumnRefMut<'_, T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.flag.set(0);
+    }
+}
+
+macro_rules! impl_n {
+    ($cell:ident, $vec:ident, $slice:ident, $n:literal) => { seq!(I in 0..$n {
+        /// A vector whose columns can be borrowed individually through a shared
+        /// reference, with the borrows checked at runtime.
+        pub struct $cell<#(T~I,)* A: Allocator = Global> {
+            vec: UnsafeCell<$vec<#(T~I,)* A>>,
+            borrows: [Cell<isize>; $n],
+        }
+
+        impl<#(T~I,)* A: Allocator> $cell<#(T~I,)* A> {
+            /// Wraps the given vector.
+            #[inline]
+            pub fn new(vec: $vec<#(T~I,)* A>) -> Self {
+                Self {
+                    vec: UnsafeCell::new(vec),
+                    borrows: Default::default(),
+                }
+            }
+
+            /// Unwraps the vector.
+            #[inline]
+            pub fn into_inner(self) -> $vec<#(T~I,)* A> {
+                self.vec.into_inner()
+            }
+
+            /// Returns the vector for unchecked access.
+            ///
+            /// This requires a mutable reference, so no column can be borrowed.
+            #[inline(always)]
+            pub fn get_mut(&mut self) -> &mut $vec<#(T~I,)* A> {
+                self.vec.get_mut()
+            }
+
+            /// Returns the number of rows in the vector.
+            #[inline(always)]
+            pub fn len(&self) -> usize {
+                self.vec().len()
+            }
+
+            /// Returns `true` if the vector has no rows.
+            #[inline(always)]
+            pub fn is_empty(&self) -> bool {
+                self.vec().is_empty()
+            }
+
+            /// Borrows the column at position `K`.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if the column is currently borrowed mutably.
+            /// See [`try_borrow_column`](Self::try_borrow_column) for a non-panicking variant.
+            #[inline]
+            #[track_caller]
+            pub fn borrow_column<const K: usize>(
+                &self,
+            ) -> ColumnRef<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                match self.try_borrow_column::<K>() {
+                    Ok(column) => column,
+                    Err(err) => panic!("{}::borrow_column: {err}", stringify!($cell)),
+                }
+            }
+
+            /// Borrows the column at position `K`, or returns an error if it is
+            /// currently borrowed mutably.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if the column already has `isize::MAX` shared
+            /// borrows, like [`RefCell::try_borrow`](core::cell::RefCell::try_borrow).
+            #[inline]
+            pub fn try_borrow_column<const K: usize>(
+                &self,
+            ) -> Result<ColumnRef<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>, BorrowError>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                let flag = &self.borrows[K];
+                let borrows = flag.get();
+
+                if borrows == WRITING {
+                    return Err(BorrowError::MutablyBorrowed { column: K });
+                }
+
+                // Running out of borrow counts can only come from leaked guards, so
+                // it isn't reported as a conflicting borrow.
+                match borrows.checked_add(1) {
+                    Some(borrows) => flag.set(borrows),
+                    None => panic!(
+                        "{}::try_borrow_column: too many shared borrows of column {K}",
+                        stringify!($cell)
+                    ),
+                }
+                let column = NonNull::from(SoaColumn::<K>::column_at(&self.vec().slice));
+
+                Ok(ColumnRef {
+                    column,
+                    flag,
+                    lifetime: PhantomData,
+                })
+            }
+
+            /// Borrows the column at position `K` mutably.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if the column is currently borrowed. See
+            /// [`try_borrow_column_mut`](Self::try_borrow_column_mut) for a
+            /// non-panicking variant.
+            #[inline]
+            #[track_caller]
+            pub fn borrow_column_mut<const K: usize>(
+                &self,
+            ) -> ColumnRefMut<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                match self.try_borrow_column_mut::<K>() {
+                    Ok(column) => column,
+                    Err(err) => panic!("{}::borrow_column_mut: {err}", stringify!($cell)),
+                }
+            }
+
+            /// Borrows the column at position `K` mutably, or returns an error if it
+            /// is currently borrowed.
+            #[inline]
+            pub fn try_borrow_column_mut<const K: usize>(
+                &self,
+            ) -> Result<ColumnRefMut<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>, BorrowError>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                let flag = &self.borrows[K];
+
+                if flag.get() != 0 {
+                    return Err(BorrowError::Borrowed { column: K });
+                }
+
+                flag.set(WRITING);
+                let column = unsafe {
+                    // SAFETY: This type is not Sync, and no method holds on to a reference
+                    // to the vector, so this is the only reference to it right now. The
+                    // column itself is not borrowed anywhere else, per its borrow flag.
+                    NonNull::from(SoaColumn::<K>::column_at_mut(&mut (*self.vec.get()).slice))
+                };
+
+                Ok(ColumnRefMut {
+                    column,
+                    flag,
+                    lifetime: PhantomData,
+                })
+            }
+
+            #[inline(always)]
+            fn vec(&self) -> &$vec<#(T~I,)* A> {
+                unsafe {
+                    // SAFETY: Column guards only point into the heap, never into the
+                    // vector itself, which can't be changed through a shared reference.
+                    &*self.vec.get()
+                }
+            }
+        }
+
+        impl<#(T~I,)* A: Allocator> From<$vec<#(T~I,)* A>> for $cell<#(T~I,)* A> {
+            #[inline]
+            fn from(vec: $vec<#(T~I,)* A>) -> Self {
+                Self::new(vec)
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(CellVecSoa~N, VecSoa~N, SliceSoa~N, N);
+});
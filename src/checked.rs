@@ -0,0 +1,327 @@
+//! Invariant tracking for [`RawDataPtr`](crate::data::RawDataPtr).
+//!
+//! With the `checked` feature enabled, every data pointer carries the capacity of its
+//! allocation and a bitmap of which slots are initialized. This state is shared by all
+//! shallow copies and offsets of the same allocation, so a stale copy that outlives a
+//! reallocation or deallocation is caught on its next use. Violations of the caller
+//! invariants documented on `RawDataPtr` become descriptive panics.
+//!
+//! Without the feature, [`Tracker`] is zero-sized and every check compiles to nothing.
+
+#[cfg(feature = "checked")]
+mod imp {
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// The byte pattern written over memory before it is released.
+    const POISON: u8 = 0xDD;
+
+    struct State {
+        capacity: usize,
+        init: Vec<u64>,
+        freed: bool,
+    }
+
+    impl State {
+        fn new(capacity: usize) -> Self {
+            Self {
+                capacity,
+                init: vec![0; capacity.div_ceil(64)],
+                freed: false,
+            }
+        }
+
+        #[inline]
+        fn is_init(&self, index: usize) -> bool {
+            (self.init[index / 64] & (1 << (index % 64))) != 0
+        }
+
+        #[inline]
+        fn set_init(&mut self, index: usize, init: bool) {
+            if init {
+                self.init[index / 64] |= 1 << (index % 64);
+            } else {
+                self.init[index / 64] &= !(1 << (index % 64));
+            }
+        }
+
+        fn first_init(&self, start: usize, end: usize) -> Option<usize> {
+            (start..end).find(|&i| self.is_init(i))
+        }
+
+        fn first_uninit(&self, start: usize, end: usize) -> Option<usize> {
+            (start..end).find(|&i| !self.is_init(i))
+        }
+    }
+
+    /// Shared allocation and initialization state for a `RawDataPtr`.
+    pub(crate) struct Tracker {
+        state: Arc<Mutex<State>>,
+        offset: usize,
+    }
+
+    impl Tracker {
+        pub(crate) fn new(capacity: usize) -> Self {
+            Self {
+                state: Arc::new(Mutex::new(State::new(capacity))),
+                offset: 0,
+            }
+        }
+
+        pub(crate) fn shallow_copy(&self) -> Self {
+            Self {
+                state: self.state.clone(),
+                offset: self.offset,
+            }
+        }
+
+        fn lock(&self, op: &str) -> MutexGuard<'_, State> {
+            // A panic from a previous check shouldn't hide the next violation.
+            let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.freed {
+                panic!("RawDataPtr::{op}: use of data that was deallocated or reallocated");
+            }
+            state
+        }
+
+        fn check_range(&self, state: &State, op: &str, start: usize, len: usize) -> usize {
+            let start = self.offset + start;
+            let end = start.checked_add(len).filter(|&end| end <= state.capacity);
+            match end {
+                Some(end) => end,
+                None => panic!(
+                    "RawDataPtr::{op}: range {start}..{start}+{len} is out of bounds \
+                     for capacity {}",
+                    state.capacity
+                ),
+            }
+        }
+
+        fn check_init(&self, state: &State, op: &str, start: usize, len: usize) {
+            let end = self.check_range(state, op, start, len);
+            if let Some(i) = state.first_uninit(self.offset + start, end) {
+                panic!("RawDataPtr::{op}: element at index {i} is not initialized");
+            }
+        }
+
+        /// Checks that this pointer is the base of an allocation of `capacity` elements.
+        pub(crate) fn check_base(&self, op: &str, capacity: usize) {
+            let state = self.lock(op);
+            if self.offset != 0 {
+                panic!(
+                    "RawDataPtr::{op}: called on a pointer offset by {}",
+                    self.offset
+                );
+            }
+            if state.capacity != capacity {
+                panic!(
+                    "RawDataPtr::{op}: expected an allocation of {capacity} elements, \
+                     but it has {}",
+                    state.capacity
+                );
+            }
+        }
+
+        pub(crate) fn check_grow(&self, old_capacity: usize, capacity: usize) {
+            if capacity < old_capacity {
+                panic!("RawDataPtr::grow: new capacity {capacity} is less than {old_capacity}");
+            }
+        }
+
+        pub(crate) fn check_shrink(&self, capacity: usize, old_capacity: usize) {
+            if capacity > old_capacity {
+                panic!("RawDataPtr::shrink: new capacity {capacity} exceeds {old_capacity}");
+            }
+
+            let state = self.lock("shrink");
+            if let Some(i) = state.first_init(capacity, old_capacity) {
+                panic!("RawDataPtr::shrink: element at index {i} is still initialized");
+            }
+        }
+
+        pub(crate) fn offset(&self, offset: usize) -> Self {
+            let state = self.lock("offset");
+            self.check_range(&state, "offset", offset, 0);
+            Self {
+                state: self.state.clone(),
+                offset: self.offset + offset,
+            }
+        }
+
+        pub(crate) fn check_alloc(&self, op: &str, start: usize, len: usize) {
+            let state = self.lock(op);
+            self.check_range(&state, op, start, len);
+        }
+
+        pub(crate) fn check_read(&self, op: &str, start: usize, len: usize) {
+            let state = self.lock(op);
+            self.check_init(&state, op, start, len);
+        }
+
+        pub(crate) fn on_write(&self, index: usize) {
+            let mut state = self.lock("write");
+            self.check_range(&state, "write", index, 1);
+            let index = self.offset + index;
+            if state.is_init(index) {
+                panic!("RawDataPtr::write: element at index {index} is already initialized");
+            }
+            state.set_init(index, true);
+        }
+
+        pub(crate) fn set_init(&self, start: usize, len: usize, init: bool) {
+            let mut state = self.lock("set_init");
+            let end = self.check_range(&state, "set_init", start, len);
+            for i in (self.offset + start)..end {
+                state.set_init(i, init);
+            }
+        }
+
+        pub(crate) fn on_swap_remove(&self, index: usize, len: usize) {
+            let mut state = self.lock("swap_remove");
+            if index >= len {
+                panic!("RawDataPtr::swap_remove: index {index} is out of bounds for len {len}");
+            }
+            self.check_init(&state, "swap_remove", 0, len);
+            state.set_init(self.offset + len - 1, false);
+        }
+
+        pub(crate) fn on_insert(&self, index: usize, len: usize) {
+            let mut state = self.lock("insert");
+            if index > len {
+                panic!("RawDataPtr::insert: index {index} is out of bounds for len {len}");
+            }
+            self.check_range(&state, "insert", 0, len + 1);
+            self.check_init(&state, "insert", 0, len);
+            state.set_init(self.offset + len, true);
+        }
+
+        pub(crate) fn on_drop(&self, len: usize) {
+            let mut state = self.lock("drop_to");
+            self.check_init(&state, "drop_to", 0, len);
+            for i in self.offset..(self.offset + len) {
+                state.set_init(i, false);
+            }
+        }
+
+        /// Replaces this pointer's state after a successful reallocation, carrying over
+        /// the initialization bitmap and invalidating every other copy of the old state.
+        pub(crate) fn on_realloc(&mut self, capacity: usize) {
+            let mut old = self.lock("grow");
+            let mut new = State::new(capacity);
+            for i in 0..usize::min(old.capacity, capacity) {
+                new.set_init(i, old.is_init(i));
+            }
+            old.freed = true;
+            drop(old);
+            *self = Self {
+                state: Arc::new(Mutex::new(new)),
+                offset: 0,
+            };
+        }
+
+        /// Replaces this pointer's state after a deallocation, invalidating every other
+        /// copy of the old state.
+        pub(crate) fn on_dealloc(&mut self) {
+            self.lock("dealloc").freed = true;
+            *self = Self::new(0);
+        }
+
+        /// Checks that exactly the elements in `0..len` of an allocation of `capacity`
+        /// elements are initialized.
+        pub(crate) fn validate(&self, capacity: usize, len: usize) {
+            self.check_base("validate", capacity);
+            let state = self.lock("validate");
+            if let Some(i) = state.first_uninit(0, len) {
+                panic!("RawDataPtr::validate: element at index {i} is not initialized");
+            }
+            if let Some(i) = state.first_init(len, capacity) {
+                panic!("RawDataPtr::validate: element at index {i} is initialized past len {len}");
+            }
+        }
+
+        /// Overwrites `size` bytes at `ptr` with a poison pattern before they are released.
+        ///
+        /// # Safety
+        ///
+        /// It is up to the caller to guarantee that `ptr` is valid for `size` bytes of writes.
+        pub(crate) unsafe fn poison(ptr: *mut u8, size: usize) {
+            unsafe { core::ptr::write_bytes(ptr, POISON, size) }
+        }
+    }
+}
+
+#[cfg(not(feature = "checked"))]
+mod imp {
+    /// Zero-sized stand-in for the `checked` feature's tracker. Every check is a no-op.
+    pub(crate) struct Tracker;
+
+    #[allow(unused_variables)]
+    impl Tracker {
+        #[inline(always)]
+        pub(crate) fn new(capacity: usize) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn shallow_copy(&self) -> Self {
+            Self
+        }
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) fn check_base(&self, op: &str, capacity: usize) {}
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) fn check_grow(&self, old_capacity: usize, capacity: usize) {}
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) fn check_shrink(&self, capacity: usize, old_capacity: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn offset(&self, offset: usize) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn check_alloc(&self, op: &str, start: usize, len: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn check_read(&self, op: &str, start: usize, len: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn on_write(&self, index: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn set_init(&self, start: usize, len: usize, init: bool) {}
+
+        #[inline(always)]
+        pub(crate) fn on_swap_remove(&self, index: usize, len: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn on_insert(&self, index: usize, len: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn on_drop(&self, len: usize) {}
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) fn on_realloc(&mut self, capacity: usize) {}
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) fn on_dealloc(&mut self) {}
+
+        #[inline(always)]
+        pub(crate) fn validate(&self, capacity: usize, len: usize) {}
+
+        #[cfg(feature = "alloc")]
+        #[inline(always)]
+        pub(crate) unsafe fn poison(ptr: *mut u8, size: usize) {}
+    }
+}
+
+pub(crate) use imp::Tracker;
@@ -0,0 +1,334 @@
+//! Append-only tables that many threads can push onto through a shared reference.
+//!
+//! A `ConcurrentVecSoaN` stores its rows in segments that double in size, so rows
+//! never move once written. Pushing reserves an index with a single atomic
+//! increment, allocates the segment for that index if no other thread has yet, and
+//! writes the row in place. Readers take a [`snapshot`](ConcurrentVecSoa2::snapshot)
+//! of the prefix of rows that have been completely written, which stays valid while
+//! other threads keep pushing.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use seq_macro::seq;
+
+use crate::data::{DropGuard, RawDataPtr};
+use crate::slice::*;
+
+/// The first segment holds `1 << FIRST_SHIFT` rows, and each one after that twice
+/// as many as the one before.
+const FIRST_SHIFT: u32 = 5;
+const FIRST_CAPACITY: usize = 1 << FIRST_SHIFT;
+
+/// Enough segments to address every index up to `usize::MAX - FIRST_CAPACITY`.
+const SEGMENTS: usize = (usize::BITS - FIRST_SHIFT) as usize;
+
+/// Returns the segment holding the row at `index`, and the row's offset within it.
+#[inline(always)]
+fn locate(index: usize) -> (usize, usize) {
+    let shifted = index
+        .checked_add(FIRST_CAPACITY)
+        .expect("capacity overflow");
+    let segment = (usize::BITS - 1 - shifted.leading_zeros() - FIRST_SHIFT) as usize;
+    (segment, shifted - segment_start(segment) - FIRST_CAPACITY)
+}
+
+/// Returns the number of rows held by `segment`.
+#[inline(always)]
+fn segment_capacity(segment: usize) -> usize {
+    FIRST_CAPACITY << segment
+}
+
+/// Returns the index of the first row held by `segment`.
+#[inline(always)]
+fn segment_start(segment: usize) -> usize {
+    segment_capacity(segment) - FIRST_CAPACITY
+}
+
+macro_rules! impl_n {
+    (
+        $concurrent:ident,
+        $snapshot:ident,
+        $segment:ident,
+        $slice:ident,
+        $slice_ref:ident,
+        $n:literal
+    ) => { seq!(I in 0..$n {
+        struct $segment<#(T~I,)*> {
+            data: (#(RawDataPtr<T~I>,)*),
+            ready: Vec<AtomicBool>,
+        }
+
+        impl<#(T~I,)*> $segment<#(T~I,)*> {
+            fn new(capacity: usize) -> Self {
+                Self {
+                    data: (#(RawDataPtr::with_capacity(capacity),)*),
+                    ready: (0..capacity).map(|_| AtomicBool::new(false)).collect(),
+                }
+            }
+        }
+
+        impl<#(T~I,)*> Drop for $segment<#(T~I,)*> {
+            fn drop(&mut self) {
+                let capacity = self.ready.len();
+
+                unsafe {
+                    // SAFETY: Every column was allocated with exactly this capacity, and
+                    // the owning vector has already dropped the rows.
+                    #(self.data.I.dealloc(capacity);)*
+                }
+            }
+        }
+
+        /// An append-only vector that any number of threads can push onto at once.
+        ///
+        /// Rows are never moved, reordered or removed while the vector is shared, so
+        /// a [`snapshot`](Self::snapshot) of the rows written so far stays valid while
+        /// other threads keep pushing.
+        pub struct $concurrent<#(T~I,)*> {
+            segments: [AtomicPtr<$segment<#(T~I,)*>>; SEGMENTS],
+            reserved: AtomicUsize,
+            published: AtomicUsize,
+            marker: PhantomData<*const (#(T~I,)*)>,
+        }
+
+        // SAFETY: Rows are moved in from whichever thread pushes them, and shared
+        // with whichever threads read a snapshot.
+        unsafe impl<#(T~I: Send,)*> Send for $concurrent<#(T~I,)*> {}
+        unsafe impl<#(T~I: Send + Sync,)*> Sync for $concurrent<#(T~I,)*> {}
+
+        impl<#(T~I,)*> $concurrent<#(T~I,)*> {
+            /// Constructs a new, empty vector.
+            ///
+            /// The vector will not allocate until rows are pushed onto it.
+            #[inline]
+            pub fn new() -> Self {
+                Self {
+                    segments: [const { AtomicPtr::new(ptr::null_mut()) }; SEGMENTS],
+                    reserved: AtomicUsize::new(0),
+                    published: AtomicUsize::new(0),
+                    marker: PhantomData,
+                }
+            }
+
+            /// Pushes a new row onto the end of the vector, returning its index.
+            ///
+            /// The row becomes visible to snapshots once every row before it has been
+            /// written too.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if the new segment's allocation size would be
+            /// greater than `isize::MAX`.
+            #[inline]
+            pub fn push(&self, row: (#(T~I,)*)) -> usize {
+                let index = self.reserved.fetch_add(1, Ordering::Relaxed);
+                let (segment, offset) = locate(index);
+                let segment = self.segment(segment);
+
+                unsafe {
+                    // SAFETY: The index was reserved by us alone, so nothing else reads
+                    // or writes this row until its ready flag is set.
+                    #(segment.data.I.shallow_copy().write(offset, row.I);)*
+                }
+
+                segment.ready[offset].store(true, Ordering::Release);
+                index
+            }
+
+            /// Returns the number of rows that have been reserved by a push, including
+            /// any that are still being written.
+            #[inline(always)]
+            pub fn reserved_len(&self) -> usize {
+                self.reserved.load(Ordering::Relaxed)
+            }
+
+            /// Returns the number of rows in the prefix of rows that have all been
+            /// completely written.
+            #[inline]
+            pub fn len(&self) -> usize {
+                let start = self.published.load(Ordering::Acquire);
+                let reserved = self.reserved.load(Ordering::Relaxed);
+
+                let mut len = start;
+                while len < reserved {
+                    let (segment, offset) = locate(len);
+                    let segment = self.segments[segment].load(Ordering::Acquire);
+
+                    // SAFETY: Segments are never freed while the vector is shared.
+                    if segment.is_null() || !unsafe { &*segment }.ready[offset].load(Ordering::Acquire) {
+                        break;
+                    }
+
+                    len += 1;
+                }
+
+                if len > start {
+                    self.published.fetch_max(len, Ordering::AcqRel);
+                }
+
+                len
+            }
+
+            /// Returns `true` if no row has been completely written yet.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Returns a view of every row that has been completely written so far.
+            #[inline]
+            pub fn snapshot(&self) -> $snapshot<'_, #(T~I,)*> {
+                $snapshot {
+                    vec: self,
+                    len: self.len(),
+                }
+            }
+
+            /// Returns the segment at `index`, allocating it if needed.
+            fn segment(&self, index: usize) -> &$segment<#(T~I,)*> {
+                let slot = &self.segments[index];
+                let existing = slot.load(Ordering::Acquire);
+
+                if !existing.is_null() {
+                    // SAFETY: Segments are never freed while the vector is shared.
+                    return unsafe { &*existing };
+                }
+
+                let new = Box::into_raw(Box::new(
+                    $segment::new(segment_capacity(index)),
+                ));
+
+                match slot.compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+                    // SAFETY: We just published this segment, and it is never freed while
+                    // the vector is shared.
+                    Ok(_) => unsafe { &*new },
+                    Err(existing) => {
+                        // Another thread allocated this segment first.
+                        // SAFETY: Nothing else has seen our segment.
+                        drop(unsafe { Box::from_raw(new) });
+                        // SAFETY: Segments are never freed while the vector is shared.
+                        unsafe { &*existing }
+                    }
+                }
+            }
+        }
+
+        impl<#(T~I,)*> Default for $concurrent<#(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<#(T~I,)*> Drop for $concurrent<#(T~I,)*> {
+            fn drop(&mut self) {
+                // Only the written prefix is dropped. Rows after a row whose push never
+                // completed (because allocating its segment panicked) are leaked.
+                let len = self.len();
+
+                for (index, slot) in self.segments.iter_mut().enumerate() {
+                    let segment = *slot.get_mut();
+                    if segment.is_null() {
+                        continue;
+                    }
+
+                    // SAFETY: We own every segment, and nothing else can access them now.
+                    let segment = unsafe { Box::from_raw(segment) };
+                    let start = segment_start(index);
+                    let rows = len.saturating_sub(start).min(segment_capacity(index));
+
+                    let _guards = unsafe {
+                        // SAFETY: Every row in the written prefix is valid, and nothing
+                        // else can access it now. The segment itself is released after
+                        // the guards, even if a destructor panics.
+                        (#(DropGuard::new(&segment.data.I, rows),)*)
+                    };
+                }
+            }
+        }
+
+        /// A view of the rows of a concurrent vector that had been completely written
+        /// when the view was taken.
+        pub struct $snapshot<'a, #(T~I,)*> {
+            vec: &'a $concurrent<#(T~I,)*>,
+            len: usize,
+        }
+
+        impl<'a, #(T~I,)*> $snapshot<'a, #(T~I,)*> {
+            /// Returns the number of rows in this view.
+            #[inline(always)]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            /// Returns `true` if this view has no rows.
+            #[inline(always)]
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// Returns the row at `index`, or `None` if it is out of bounds.
+            #[inline]
+            pub fn get(&self, index: usize) -> Option<(#(&'a T~I,)*)> {
+                if index >= self.len {
+                    return None;
+                }
+
+                let (segment, offset) = locate(index);
+                let segment = self.segment(segment);
+
+                unsafe {
+                    // SAFETY: Every row in the view is valid, and never written again
+                    // while the vector is shared.
+                    Some((#(&*segment.data.I.data_at(offset),)*))
+                }
+            }
+
+            /// Returns an iterator over contiguous slices of this view's rows, one per
+            /// segment, in order.
+            #[inline]
+            pub fn segments(&self) -> impl Iterator<Item = $slice_ref<'a, #(T~I,)*>> + '_ {
+                (0..SEGMENTS)
+                    .take_while(|&index| segment_start(index) < self.len)
+                    .map(|index| {
+                        let segment = self.segment(index);
+                        let len = usize::min(self.len - segment_start(index), segment_capacity(index));
+
+                        $slice_ref {
+                            slice: $slice {
+                                len,
+                                data: (#(segment.data.I.shallow_copy(),)*),
+                            },
+                            lifetime: PhantomData,
+                        }
+                    })
+            }
+
+            /// Returns the segment at `index`, which holds at least one row of the view.
+            #[inline(always)]
+            fn segment(&self, index: usize) -> &'a $segment<#(T~I,)*> {
+                let segment = self.vec.segments[index].load(Ordering::Acquire);
+                debug_assert!(!segment.is_null());
+
+                // SAFETY: Every segment holding a row of the view has been allocated,
+                // and is never freed while the vector is shared.
+                unsafe { &*segment }
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(
+        ConcurrentVecSoa~N,
+        ConcurrentSnapshot~N,
+        ConcurrentSegment~N,
+        SliceSoa~N,
+        SliceSoaRef~N,
+        N
+    );
+});
@@ -0,0 +1,320 @@
+//! Copy-on-write tables with cheap clones.
+//!
+//! Every column of a `CowVecSoaN` is a reference-counted buffer, so cloning the
+//! table only bumps one count per column. A column is copied the first time it is
+//! mutated while another clone still shares it, and only that column: edits that
+//! touch one or two columns of a wide table leave the others shared.
+//!
+//! The table derefs to a read-only slice. Rows are edited one column at a time
+//! through [`column_mut`](CowVecSoa2::column_mut), which unshares only the column it
+//! returns, so there is no mutable slice that would have to copy every column up
+//! front. Operations that change the length, such as `push`, copy every shared column.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+use seq_macro::seq;
+
+use crate::data::RawDataPtr;
+use crate::slice::*;
+
+/// Returns a data pointer to the `len` elements of a column buffer at `ptr`.
+///
+/// The pointer must come from [`Vec::as_mut_ptr`] before any write through it.
+#[inline(always)]
+fn attach<T>(ptr: *const T, len: usize) -> RawDataPtr<T> {
+    let data = unsafe {
+        // SAFETY: The pointer comes from the column's buffer, which is valid for its
+        // length, and is owned and released by the column.
+        RawDataPtr::from_block(NonNull::new_unchecked(ptr.cast_mut()).cast(), len)
+    };
+
+    data.mark_init(0, len);
+    data
+}
+
+/// Returns the column, copied first if it is shared, and a data pointer to it that
+/// can be written through.
+#[inline(always)]
+fn make_mut<T: Clone>(column: &mut Arc<Vec<T>>) -> (&mut Vec<T>, RawDataPtr<T>) {
+    let column = Arc::make_mut(column);
+    let data = attach(column.as_mut_ptr(), column.len());
+    (column, data)
+}
+
+/// Returns a data pointer to the column, which can be written through if the column
+/// isn't shared.
+#[inline(always)]
+fn reattach<T>(column: &mut Arc<Vec<T>>) -> RawDataPtr<T> {
+    match Arc::get_mut(column) {
+        Some(column) => attach(column.as_mut_ptr(), column.len()),
+        None => attach(column.as_ptr(), column.len()),
+    }
+}
+
+/// Points a table's data pointers back at its columns when dropped.
+///
+/// Operations that replace or resize columns hold this guard, so the data pointers
+/// are never left pointing at a released buffer, even if a clone, allocation or
+/// destructor panics partway through.
+struct ResyncGuard<'a, C> {
+    table: &'a mut C,
+    resync: fn(&mut C),
+}
+
+impl<C> Drop for ResyncGuard<'_, C> {
+    #[inline]
+    fn drop(&mut self) {
+        (self.resync)(self.table);
+    }
+}
+
+/// Truncates a column to `len` rows when dropped, replacing it with `copy` if one
+/// was made because the column was shared.
+///
+/// Every column is truncated by its own guard, so the columns are all left with the
+/// same length even if dropping the rows of one of them panics.
+struct TruncateGuard<'a, T: Clone> {
+    column: &'a mut Arc<Vec<T>>,
+    copy: Option<Arc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T: Clone> Drop for TruncateGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        match self.copy.take() {
+            // Replace the column before releasing the old one, whose rows may panic.
+            Some(copy) => drop(mem::replace(self.column, copy)),
+            // The column isn't shared, so this never copies it.
+            None => Arc::make_mut(self.column).truncate(self.len),
+        }
+    }
+}
+
+/// Access to the column at position `K` of a copy-on-write table, for code that is
+/// generic over which column it touches.
+pub trait CowColumn<const K: usize> {
+    /// The element type of the column.
+    type Elem;
+
+    /// Returns whether the column is shared with another clone of the table.
+    fn is_column_shared(&self) -> bool;
+
+    /// Returns the column at position `K`, copying it first if it is shared.
+    fn column_mut_at(&mut self) -> &mut [Self::Elem]
+    where
+        Self::Elem: Clone;
+}
+
+macro_rules! impl_n {
+    ($cow:ident, $slice:ident, $n:literal) => { seq!(I in 0..$n {
+        /// A vector whose columns are shared between clones, and copied one at a time
+        /// when they are first mutated.
+        pub struct $cow<#(T~I,)*> {
+            columns: (#(Arc<Vec<T~I>>,)*),
+            slice: $slice<#(T~I,)*>,
+        }
+
+        impl<#(T~I,)*> $cow<#(T~I,)*> {
+            /// Constructs a new, empty vector.
+            #[inline]
+            pub fn new() -> Self {
+                Self::from_columns((#(Vec::new(),)*))
+            }
+
+            /// Builds a vector from columns of equal length.
+            fn from_columns(columns: (#(Vec<T~I>,)*)) -> Self {
+                let len = columns.0.len();
+                #(debug_assert_eq!(columns.I.len(), len);)*
+
+                let columns = (#(Arc::new(columns.I),)*);
+                let data = (#(attach(columns.I.as_ptr(), columns.I.len()),)*);
+
+                Self {
+                    columns,
+                    slice: $slice { len, data },
+                }
+            }
+
+            /// Returns whether the column at position `K` is shared with another clone.
+            #[inline(always)]
+            pub fn is_column_shared<const K: usize>(&self) -> bool
+            where
+                Self: CowColumn<K>,
+            {
+                CowColumn::<K>::is_column_shared(self)
+            }
+
+            /// Returns the column at position `K`, copying only that column first if it
+            /// is shared with another clone.
+            #[inline]
+            pub fn column_mut<const K: usize>(&mut self) -> &mut [<Self as CowColumn<K>>::Elem]
+            where
+                Self: CowColumn<K>,
+                <Self as CowColumn<K>>::Elem: Clone,
+            {
+                CowColumn::<K>::column_mut_at(self)
+            }
+
+            /// Returns the number of columns shared with another clone.
+            #[inline]
+            pub fn shared_columns(&self) -> usize {
+                0 #(+ usize::from(Arc::strong_count(&self.columns.I) > 1))*
+            }
+
+            /// Points every data pointer at its column, and sets the length to that of
+            /// the columns, which is the same for every column.
+            fn resync(&mut self) {
+                self.slice.len = [#(self.columns.I.len(),)*].into_iter().min().unwrap_or(0);
+                #(self.slice.data.I = reattach(&mut self.columns.I);)*
+            }
+        }
+
+        impl<#(T~I: Clone,)*> $cow<#(T~I,)*> {
+            /// Appends a row to the back of the vector, copying every shared column
+            /// first.
+            pub fn push(&mut self, row: (#(T~I,)*)) {
+                let this = ResyncGuard { table: self, resync: Self::resync };
+
+                // Copy and reserve every column first, so that a panicking clone or a
+                // failed allocation can't leave the columns with different lengths.
+                #(Arc::make_mut(&mut this.table.columns.I).reserve(1);)*
+                #(Arc::make_mut(&mut this.table.columns.I).push(row.I);)*
+            }
+
+            /// Removes the last row and returns it, or `None` if the vector is empty.
+            ///
+            /// Every shared column is copied first.
+            pub fn pop(&mut self) -> Option<(#(T~I,)*)> {
+                if self.slice.len == 0 {
+                    return None;
+                }
+
+                let this = ResyncGuard { table: self, resync: Self::resync };
+
+                // Copy every column first, as in push.
+                #(Arc::make_mut(&mut this.table.columns.I);)*
+                Some((#(Arc::make_mut(&mut this.table.columns.I).pop().unwrap(),)*))
+            }
+
+            /// Shortens the vector to `len` rows, dropping the rest.
+            ///
+            /// Every shared column is copied first. This has no effect if `len` is
+            /// greater than or equal to the current length.
+            pub fn truncate(&mut self, len: usize) {
+                if len >= self.slice.len {
+                    return;
+                }
+
+                // Shared columns are replaced rather than copied, since the copy would
+                // be truncated anyway. Every replacement is made before any column
+                // changes, so that a panicking clone leaves the vector as it was.
+                let copies = (#(
+                    match Arc::get_mut(&mut self.columns.I) {
+                        Some(_) => None,
+                        None => Some(Arc::new(self.columns.I[..len].to_vec())),
+                    },
+                )*);
+
+                let this = ResyncGuard { table: self, resync: Self::resync };
+                let _columns = (#(
+                    TruncateGuard {
+                        column: &mut this.table.columns.I,
+                        copy: copies.I,
+                        len,
+                    },
+                )*);
+            }
+
+            /// Removes every row from the vector.
+            ///
+            /// Shared columns are released rather than copied.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.truncate(0);
+            }
+        }
+
+        impl<#(T~I,)*> Clone for $cow<#(T~I,)*> {
+            /// Clones the vector in O(columns) time, sharing every column.
+            #[inline]
+            fn clone(&self) -> Self {
+                let columns = (#(Arc::clone(&self.columns.I),)*);
+                let data = (#(attach(columns.I.as_ptr(), columns.I.len()),)*);
+
+                Self {
+                    columns,
+                    slice: $slice {
+                        len: self.slice.len,
+                        data,
+                    },
+                }
+            }
+        }
+
+        impl<#(T~I,)*> Default for $cow<#(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<#(T~I,)*> FromIterator<(#(T~I,)*)> for $cow<#(T~I,)*> {
+            fn from_iter<It: IntoIterator<Item = (#(T~I,)*)>>(iter: It) -> Self {
+                let mut columns = (#(Vec::<T~I>::new(),)*);
+                for row in iter {
+                    #(columns.I.push(row.I);)*
+                }
+
+                Self::from_columns(columns)
+            }
+        }
+
+        impl<#(T~I,)*> Deref for $cow<#(T~I,)*> {
+            type Target = $slice<#(T~I,)*>;
+
+            #[inline(always)]
+            fn deref(&self) -> &Self::Target {
+                &self.slice
+            }
+        }
+    });}
+}
+
+macro_rules! impl_column_n {
+    ($cow:ident, $n:literal) => { seq!(I in 0..$n {
+        impl_column_k!($cow, $n, (#(T~I,)*));
+    });}
+}
+
+macro_rules! impl_column_k {
+    ($cow:ident, $n:literal, ($($t:ident,)*)) => { seq!(K in 0..$n {
+        impl<$($t,)*> CowColumn<K> for $cow<$($t,)*> {
+            type Elem = T~K;
+
+            #[inline(always)]
+            fn is_column_shared(&self) -> bool {
+                Arc::strong_count(&self.columns.K) > 1
+            }
+
+            #[inline]
+            fn column_mut_at(&mut self) -> &mut [T~K]
+            where
+                T~K: Clone,
+            {
+                let (column, data) = make_mut(&mut self.columns.K);
+                self.slice.data.K = data;
+                column
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(CowVecSoa~N, SliceSoa~N, N);
+    impl_column_n!(CowVecSoa~N, N);
+});
@@ -1,9 +1,19 @@
-use std::alloc::{self, Layout};
-use std::mem::{self, MaybeUninit};
-use std::ptr::{self, NonNull};
-use std::slice;
+use core::mem::{self, MaybeUninit};
+use core::ptr::{self, NonNull};
+use core::slice;
 
-pub struct RawDataPtr<T>(NonNull<MaybeUninit<T>>);
+use allocator_api2::alloc::Layout;
+#[cfg(feature = "alloc")]
+use allocator_api2::alloc::{handle_alloc_error, AllocError, Allocator, Global};
+
+use crate::checked::Tracker;
+#[cfg(feature = "alloc")]
+use crate::error::TryReserveError;
+
+pub struct RawDataPtr<T> {
+    ptr: NonNull<MaybeUninit<T>>,
+    check: Tracker,
+}
 
 unsafe impl<T> Send for RawDataPtr<T> where T: Send {}
 unsafe impl<T> Sync for RawDataPtr<T> where T: Sync {}
@@ -17,32 +27,198 @@ impl<T> RawDataPtr<T> {
     ///
     /// This operation will panic if there is not enough memory to perform the new
     /// allocation, or if the resulting allocation size is greater than `isize::MAX`.
+    #[cfg(feature = "alloc")]
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, 1, &Global)
+    }
+
+    /// Allocates a new data array with the given capacity, if any.
+    ///
+    /// If `T` is zero-sized, or the given capacity is 0, this will not allocate.
+    ///
+    /// Returns an error if there is not enough memory to perform the new allocation,
+    /// or if the resulting allocation size is greater than `isize::MAX`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, 1, &Global)
+    }
+
+    /// Allocates a new data array with the given capacity, if any, from `alloc`.
+    ///
+    /// The data block is aligned to at least `align` bytes, which must be a power of
+    /// two (use 1 for the natural alignment of `T`). The same alignment must be passed
+    /// to every other operation on this data block.
+    ///
+    /// See [`with_capacity`](Self::with_capacity) for details.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn with_capacity_in<A: Allocator>(capacity: usize, align: usize, alloc: &A) -> Self {
+        match Self::try_with_capacity_in(capacity, align, alloc) {
+            Ok(data) => data,
+            Err(err) => handle_reserve_error(err),
+        }
+    }
+
+    /// Allocates a new data array with the given capacity, if any, from `alloc`.
+    ///
+    /// The data block is aligned to at least `align` bytes, which must be a power of
+    /// two (use 1 for the natural alignment of `T`). The same alignment must be passed
+    /// to every other operation on this data block.
+    ///
+    /// See [`try_with_capacity`](Self::try_with_capacity) for details.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn try_with_capacity_in<A: Allocator>(
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) -> Result<Self, TryReserveError> {
+        Self::try_alloc_in(capacity, align, alloc, false)
+    }
+
+    /// Allocates a new, zero-filled data array with the given capacity, if any, from `alloc`.
+    ///
+    /// The memory comes from [`Allocator::allocate_zeroed`], so the allocator can hand
+    /// out pages that are already zeroed instead of filling them. The elements are
+    /// still reported as uninitialized until they are marked otherwise.
+    ///
+    /// See [`with_capacity_in`](Self::with_capacity_in) for details.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn with_capacity_zeroed_in<A: Allocator>(capacity: usize, align: usize, alloc: &A) -> Self {
+        match Self::try_with_capacity_zeroed_in(capacity, align, alloc) {
+            Ok(data) => data,
+            Err(err) => handle_reserve_error(err),
+        }
+    }
+
+    /// Allocates a new, zero-filled data array with the given capacity, if any, from `alloc`.
+    ///
+    /// See [`with_capacity_zeroed_in`](Self::with_capacity_zeroed_in) and
+    /// [`try_with_capacity`](Self::try_with_capacity) for details.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn try_with_capacity_zeroed_in<A: Allocator>(
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) -> Result<Self, TryReserveError> {
+        Self::try_alloc_in(capacity, align, alloc, true)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn try_alloc_in<A: Allocator>(
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+        zeroed: bool,
+    ) -> Result<Self, TryReserveError> {
         if (mem::size_of::<T>() == 0) || (capacity == 0) {
-            return Self(NonNull::dangling());
+            return Ok(Self::from_parts(
+                NonNull::dangling(),
+                Tracker::new(capacity),
+            ));
         }
 
-        let layout = new_layout::<T>(capacity);
+        let layout = try_new_layout::<T>(capacity, align)?;
 
         debug_assert!(capacity > 0);
         debug_assert!(layout.size() > 0);
 
-        assert!(layout.size() <= isize::MAX as usize, "allocation too large");
+        let result = match zeroed {
+            true => alloc.allocate_zeroed(layout),
+            false => alloc.allocate(layout),
+        };
+
+        let ptr = resolve_ptr(result, layout)?;
+        Ok(Self::from_parts(ptr, Tracker::new(capacity)))
+    }
 
-        unsafe { Self(resolve_ptr(alloc::alloc(layout), layout)) }
+    #[inline(always)]
+    fn from_parts(ptr: NonNull<MaybeUninit<T>>, check: Tracker) -> Self {
+        Self { ptr, check }
+    }
+
+    /// Creates a pointer to a column of `capacity` elements inside a larger block.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `ptr` is aligned for `T` and valid for `capacity` elements (or `T` is a ZST)
+    /// - The memory is owned and eventually released by the caller, not this pointer
+    #[inline(always)]
+    pub(crate) unsafe fn from_block(ptr: NonNull<u8>, capacity: usize) -> Self {
+        Self::from_parts(ptr.cast(), Tracker::new(capacity))
+    }
+
+    /// Detaches this pointer from a block that the caller is about to release.
+    ///
+    /// The pointer is left dangling, and with the `checked` feature enabled, every
+    /// other copy of it will panic on its next use.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    pub(crate) fn release_block(&mut self) {
+        self.check.on_dealloc();
+        self.ptr = NonNull::dangling();
+    }
+
+    /// Returns whether this pointer currently points to the given address.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub(crate) fn points_to(&self, ptr: NonNull<MaybeUninit<T>>) -> bool {
+        self.ptr == ptr
     }
 
     /// Shallow-copies this pointer, returning a pointer to the same data in memory.
     #[inline(always)]
     pub fn shallow_copy(&self) -> Self {
-        Self(self.0)
+        Self::from_parts(self.ptr, self.check.shallow_copy())
     }
 
     /// Returns the raw pointer for this storage, which may be dangling or uninitialized.
     #[inline(always)]
     pub fn raw_ptr(&mut self) -> NonNull<MaybeUninit<T>> {
-        self.0
+        self.ptr
+    }
+
+    /// Marks the elements in the range `start..(start+len)` as initialized.
+    ///
+    /// This has no effect unless the `checked` feature is enabled, where it records that
+    /// the elements were written through [`raw_data`](Self::raw_data) or [`raw_ptr`](Self::raw_ptr).
+    #[inline(always)]
+    pub fn mark_init(&self, start: usize, len: usize) {
+        self.check.set_init(start, len, true);
+    }
+
+    /// Marks the elements in the range `start..(start+len)` as uninitialized.
+    ///
+    /// This has no effect unless the `checked` feature is enabled, where it records that
+    /// the elements were moved out or forgotten without going through this pointer.
+    #[inline(always)]
+    pub fn mark_uninit(&self, start: usize, len: usize) {
+        self.check.set_init(start, len, false);
+    }
+
+    /// Fills the elements in the range `start..(start+len)` with zero bytes.
+    ///
+    /// This doesn't drop any previous values or mark the elements as initialized.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - The range `start..(start+len)` is allocated in this pointer
+    /// - No element in that range holds a value that still needs to be dropped
+    #[inline(always)]
+    pub unsafe fn write_zeroed(&mut self, start: usize, len: usize) {
+        self.check.check_alloc("write_zeroed", start, len);
+
+        unsafe {
+            // SAFETY: The caller guarantees that this range is allocated.
+            ptr::write_bytes(self.ptr.as_ptr().add(start), 0, len);
+        }
     }
 
     /// Returns a raw pointer to the data at the given index.
@@ -51,6 +227,8 @@ impl<T> RawDataPtr<T> {
     /// - The `index` points to valid and initialized data from this pointer.
     #[inline(always)]
     pub fn data_at(&self, index: usize) -> *const T {
+        self.check.check_read("data_at", index, 1);
+
         unsafe {
             // SAFETY: The caller guarantees that the index is within bounds, so the
             // pointer will point to allocated data. The caller also guarantees that
@@ -66,6 +244,8 @@ impl<T> RawDataPtr<T> {
     /// - The `index` points to valid and initialized data from this pointer.
     #[inline(always)]
     pub fn data_mut_at(&mut self, index: usize) -> *mut T {
+        self.check.check_read("data_mut_at", index, 1);
+
         unsafe {
             // SAFETY: The caller guarantees that the index is within bounds, so the
             // pointer will point to allocated data. The caller also guarantees that
@@ -87,35 +267,284 @@ impl<T> RawDataPtr<T> {
     ///
     /// This operation will panic if there is not enough memory to perform the new
     /// allocation, or if the resulting allocation size is greater than `isize::MAX`.
+    #[cfg(feature = "alloc")]
     #[inline]
     pub unsafe fn grow(&mut self, old_capacity: usize, capacity: usize) {
+        // SAFETY: The caller upholds the guarantees required by `grow_in`.
+        unsafe { self.grow_in(old_capacity, capacity, 1, &Global) }
+    }
+
+    /// Reallocates this array's old data block into a new data block.
+    ///
+    /// Returns an error if there is not enough memory to perform the new allocation,
+    /// or if the resulting allocation size is greater than `isize::MAX`. On error,
+    /// the old data block is left untouched.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `capacity >= old_capacity`
+    /// - This array has exactly `old_capacity` elements allocated (may be 0)
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn try_grow(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+    ) -> Result<(), TryReserveError> {
+        // SAFETY: The caller upholds the guarantees required by `try_grow_in`.
+        unsafe { self.try_grow_in(old_capacity, capacity, 1, &Global) }
+    }
+
+    /// Reallocates this array's old data block into a new data block from `alloc`.
+    ///
+    /// See [`grow`](Self::grow) for details.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the requirements of [`grow`](Self::grow), it is up to the
+    /// caller to guarantee that any existing data block was allocated by `alloc`
+    /// with the same `align`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn grow_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) {
+        unsafe {
+            // SAFETY: The caller upholds the same guarantees required by `try_grow_in`.
+            if let Err(err) = self.try_grow_in(old_capacity, capacity, align, alloc) {
+                handle_reserve_error(err);
+            }
+        }
+    }
+
+    /// Reallocates this array's old data block into a new data block from `alloc`.
+    ///
+    /// See [`try_grow`](Self::try_grow) for details.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the requirements of [`try_grow`](Self::try_grow), it is up to
+    /// the caller to guarantee that any existing data block was allocated by `alloc`
+    /// with the same `align`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn try_grow_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) -> Result<(), TryReserveError> {
+        // SAFETY: The caller upholds the guarantees required by `try_realloc_in`.
+        unsafe { self.try_realloc_in(old_capacity, capacity, align, alloc, false) }
+    }
+
+    /// Reallocates this array's old data block into a new data block from `alloc`,
+    /// with every element in `old_capacity..capacity` zero-filled.
+    ///
+    /// The memory comes from [`Allocator::grow_zeroed`] (or
+    /// [`Allocator::allocate_zeroed`] if nothing was allocated yet), so the allocator
+    /// can skip filling memory it already knows to be zeroed.
+    ///
+    /// See [`grow_in`](Self::grow_in) for details.
+    ///
+    /// # Safety
+    ///
+    /// See [`grow_in`](Self::grow_in).
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn grow_zeroed_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) {
+        unsafe {
+            // SAFETY: The caller upholds the same guarantees required by `try_grow_in`.
+            if let Err(err) = self.try_grow_zeroed_in(old_capacity, capacity, align, alloc) {
+                handle_reserve_error(err);
+            }
+        }
+    }
+
+    /// Reallocates this array's old data block into a new data block from `alloc`,
+    /// with every element in `old_capacity..capacity` zero-filled.
+    ///
+    /// See [`grow_zeroed_in`](Self::grow_zeroed_in) and [`try_grow_in`](Self::try_grow_in)
+    /// for details.
+    ///
+    /// # Safety
+    ///
+    /// See [`try_grow_in`](Self::try_grow_in).
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn try_grow_zeroed_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) -> Result<(), TryReserveError> {
+        // SAFETY: The caller upholds the guarantees required by `try_realloc_in`.
+        unsafe { self.try_realloc_in(old_capacity, capacity, align, alloc, true) }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    unsafe fn try_realloc_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+        zeroed: bool,
+    ) -> Result<(), TryReserveError> {
         debug_assert!(capacity >= old_capacity);
+        self.check.check_base("grow", old_capacity);
+        self.check.check_grow(old_capacity, capacity);
 
         if (mem::size_of::<T>() == 0) || (capacity == 0) {
-            return; // Stay dangling
+            self.check.on_realloc(capacity);
+            return Ok(()); // Stay dangling
         }
 
-        let layout = new_layout::<T>(capacity);
-        let size = layout.size();
-        debug_assert!(size > 0);
-
-        assert!(layout.size() <= isize::MAX as usize, "allocation too large");
+        let layout = try_new_layout::<T>(capacity, align)?;
+        debug_assert!(layout.size() > 0);
 
         unsafe {
             if old_capacity == 0 {
                 // SAFETY: The caller guarantees that capacity > 0.
-                self.0 = resolve_ptr(alloc::alloc(layout), layout);
+                let result = match zeroed {
+                    true => alloc.allocate_zeroed(layout),
+                    false => alloc.allocate(layout),
+                };
+
+                self.ptr = resolve_ptr(result, layout)?;
             } else {
                 // SAFETY: The caller guarantees that this is allocated.
-                let old_ptr = self.0.as_ptr() as *mut u8;
+                let old_ptr = self.ptr.cast::<u8>();
                 // SAFETY: We checked that T is not a ZST and old_capacity > 0.
-                let old_layout = Layout::array::<T>(old_capacity).unwrap();
+                let old_layout = try_new_layout::<T>(old_capacity, align).unwrap();
                 debug_assert!(old_layout.size() > 0);
 
-                // SAFETY: The caller guarantees that capacity > 0.
-                self.0 = resolve_ptr(alloc::realloc(old_ptr, old_layout, size), layout);
+                // SAFETY: The caller guarantees that capacity > 0. If this fails,
+                // the old block is still valid and self.ptr is left unchanged.
+                let result = match zeroed {
+                    true => alloc.grow_zeroed(old_ptr, old_layout, layout),
+                    false => alloc.grow(old_ptr, old_layout, layout),
+                };
+
+                self.ptr = resolve_ptr(result, layout)?;
             }
         }
+
+        self.check.on_realloc(capacity);
+        Ok(())
+    }
+
+    /// Reallocates this array's old data block into a new, smaller data block.
+    ///
+    /// If the new capacity is 0, the old data block is deallocated.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `capacity <= old_capacity`
+    /// - This array has exactly `old_capacity` elements allocated (may be 0)
+    /// - No valid elements remain in the range `capacity..old_capacity`
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if the allocator fails to perform the reallocation.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn shrink(&mut self, old_capacity: usize, capacity: usize) {
+        // SAFETY: The caller upholds the guarantees required by `shrink_in`.
+        unsafe { self.shrink_in(old_capacity, capacity, 1, &Global) }
+    }
+
+    /// Reallocates this array's old data block into a new, smaller data block from `alloc`.
+    ///
+    /// See [`shrink`](Self::shrink) for details.
+    ///
+    /// # Safety
+    ///
+    /// In addition to the requirements of [`shrink`](Self::shrink), it is up to the
+    /// caller to guarantee that any existing data block was allocated by `alloc`
+    /// with the same `align`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn shrink_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) {
+        unsafe {
+            // SAFETY: The caller upholds the same guarantees required by `try_shrink_in`.
+            if let Err(err) = self.try_shrink_in(old_capacity, capacity, align, alloc) {
+                handle_reserve_error(err);
+            }
+        }
+    }
+
+    /// Reallocates this array's old data block into a new, smaller data block from `alloc`.
+    ///
+    /// Returns an error if the allocator fails to perform the reallocation. On error,
+    /// the old data block is left unchanged.
+    ///
+    /// # Safety
+    ///
+    /// See [`shrink_in`](Self::shrink_in).
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn try_shrink_in<A: Allocator>(
+        &mut self,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &A,
+    ) -> Result<(), TryReserveError> {
+        debug_assert!(capacity <= old_capacity);
+        self.check.check_base("shrink", old_capacity);
+        self.check.check_shrink(capacity, old_capacity);
+
+        if (mem::size_of::<T>() == 0) || (capacity == old_capacity) {
+            self.check.on_realloc(capacity);
+            return Ok(()); // Nothing to reallocate
+        }
+
+        unsafe {
+            if capacity == 0 {
+                // SAFETY: The caller guarantees that this is allocated to old_capacity.
+                self.dealloc_in(old_capacity, align, alloc);
+                return Ok(());
+            }
+
+            // SAFETY: We checked that T is not a ZST and old_capacity > capacity > 0.
+            let old_ptr = self.ptr.cast::<u8>();
+            let old_layout = try_new_layout::<T>(old_capacity, align).unwrap();
+            let layout = try_new_layout::<T>(capacity, align).unwrap();
+            debug_assert!(layout.size() > 0);
+
+            // SAFETY: The tail is within the old allocation and holds no valid elements.
+            let tail = old_layout.size() - layout.size();
+            Tracker::poison(old_ptr.as_ptr().add(layout.size()), tail);
+
+            // SAFETY: The caller guarantees that this is allocated to old_capacity. If
+            // this fails, the old block is still valid and self.ptr is left unchanged.
+            self.ptr = resolve_ptr(alloc.shrink(old_ptr, old_layout, layout), layout)?;
+        }
+
+        self.check.on_realloc(capacity);
+        Ok(())
     }
 
     /// Deallocates this array's data block.
@@ -124,20 +553,40 @@ impl<T> RawDataPtr<T> {
     ///
     /// It is up to the caller to guarantee the following:
     /// - This array has exactly `capacity` elements allocated
+    #[cfg(feature = "alloc")]
     #[inline]
     pub unsafe fn dealloc(&mut self, capacity: usize) {
+        // SAFETY: The caller upholds the guarantees required by `dealloc_in`.
+        unsafe { self.dealloc_in(capacity, 1, &Global) }
+    }
+
+    /// Deallocates this array's data block back to `alloc`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - This array has exactly `capacity` elements allocated
+    /// - Any existing data block was allocated by `alloc` with the same `align`
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn dealloc_in<A: Allocator>(&mut self, capacity: usize, align: usize, alloc: &A) {
+        self.check.check_base("dealloc", capacity);
+        self.check.on_dealloc();
+
         if (mem::size_of::<T>() == 0) || (capacity == 0) {
             return; // Nothing to deallocate
         }
 
         // SAFETY: We checked that T is not a ZST and capacity > 0.
-        let layout = Layout::array::<T>(capacity).unwrap();
+        let layout = try_new_layout::<T>(capacity, align).unwrap();
         debug_assert!(layout.size() > 0);
 
         unsafe {
+            // SAFETY: The caller guarantees that this block is allocated to capacity.
+            Tracker::poison(self.ptr.as_ptr() as *mut u8, layout.size());
             // SAFETY: We know that old_layout has a nonzero size
-            alloc::dealloc(self.0.as_ptr() as *mut u8, layout);
-            self.0 = NonNull::dangling();
+            alloc.deallocate(self.ptr.cast::<u8>(), layout);
+            self.ptr = NonNull::dangling();
         }
     }
 
@@ -150,13 +599,15 @@ impl<T> RawDataPtr<T> {
     /// - The computed offset pointer, *in bytes*, does not overflow an `isize`.
     #[inline(always)]
     pub unsafe fn offset(&self, offset: usize) -> Self {
+        let check = self.check.offset(offset);
+
         if mem::size_of::<T>() == 0 {
-            return Self(NonNull::dangling());
+            return Self::from_parts(NonNull::dangling(), check);
         }
 
         unsafe {
             // SAFETY: Either this is a ZST or the caller guarantees this pointer is allocated.
-            Self(NonNull::new_unchecked(self.0.as_ptr().add(offset)))
+            Self::from_parts(NonNull::new_unchecked(self.ptr.as_ptr().add(offset)), check)
         }
     }
 
@@ -168,8 +619,10 @@ impl<T> RawDataPtr<T> {
     /// - This array has at least `len` elements allocated
     #[inline(always)]
     pub unsafe fn raw_data(&mut self, len: usize) -> &mut [MaybeUninit<T>] {
+        self.check.check_alloc("raw_data", 0, len);
+
         // SAFETY: The caller guarantees that we have at least len elements allocated.
-        unsafe { slice::from_raw_parts_mut(self.0.as_ptr(), len) }
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), len) }
     }
 
     /// Writes an element to the given index.
@@ -181,9 +634,11 @@ impl<T> RawDataPtr<T> {
     /// - The element at `index` is currently invalid
     #[inline(always)]
     pub unsafe fn write(&mut self, index: usize, val: T) {
+        self.check.on_write(index);
+
         unsafe {
             // SAFETY: The caller guarantees that this slot is allocated and invalid.
-            (*self.0.as_ptr().add(index)).write(val);
+            (*self.ptr.as_ptr().add(index)).write(val);
         }
     }
 
@@ -196,6 +651,8 @@ impl<T> RawDataPtr<T> {
     /// - All elements the range `start..(start+len)` are valid
     #[inline(always)]
     pub unsafe fn slice(&self, start: usize, len: usize) -> &[T] {
+        self.check.check_read("slice", start, len);
+
         unsafe {
             // SAFETY: Casting a `[MaybeUninit<T>]` to a `[T]` is safe because the caller
             // guarantees that this portion of the data is valid and `MaybeUninit<T>` is
@@ -203,7 +660,7 @@ impl<T> RawDataPtr<T> {
             // since it refers to memory owned by `slice` which is a reference and thus
             // guaranteed to be valid for reads.
             // Ref: https://doc.rust-lang.org/stable/src/core/mem/maybe_uninit.rs.html#972
-            slice::from_raw_parts(self.0.as_ptr().add(start) as *const T, len)
+            slice::from_raw_parts(self.ptr.as_ptr().add(start) as *const T, len)
         }
     }
 
@@ -216,11 +673,38 @@ impl<T> RawDataPtr<T> {
     /// - All elements the range `start..(start+len)` are valid
     #[inline(always)]
     pub unsafe fn slice_mut(&mut self, start: usize, len: usize) -> &mut [T] {
+        self.check.check_read("slice_mut", start, len);
+
         unsafe {
             // SAFETY: Similar to safety notes for `slice`, but we have a mutable reference
             // which is also guaranteed to be valid for writes.
             // Ref: https://doc.rust-lang.org/stable/src/core/mem/maybe_uninit.rs.html#994
-            slice::from_raw_parts_mut(self.0.as_ptr().add(start) as *mut T, len)
+            slice::from_raw_parts_mut(self.ptr.as_ptr().add(start) as *mut T, len)
+        }
+    }
+
+    /// Inserts an element at `index`, shifting the elements in `index..len` up by one.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - This pointer has allocated at least `len + 1` elements
+    /// - All elements the range `0..len` are valid
+    /// - `index <= len`
+    #[inline(always)]
+    pub unsafe fn insert(&mut self, index: usize, len: usize, val: T) {
+        self.check.on_insert(index, len);
+
+        unsafe {
+            debug_assert!(index <= len);
+
+            // SAFETY: The caller guarantees that len + 1 elements are allocated, so
+            // shifting the elements in index..len up by one stays in bounds. The ranges
+            // overlap, so this has to be a plain copy. The slot at index is then left
+            // bitwise duplicated, and can be overwritten without dropping it.
+            let array_ptr = self.ptr.as_ptr();
+            ptr::copy(array_ptr.add(index), array_ptr.add(index + 1), len - index);
+            (*array_ptr.add(index)).write(val);
         }
     }
 
@@ -235,6 +719,8 @@ impl<T> RawDataPtr<T> {
     /// - `index < len`
     #[inline(always)]
     pub unsafe fn swap_remove(&mut self, index: usize, len: usize) -> T {
+        self.check.on_swap_remove(index, len);
+
         unsafe {
             debug_assert!(len > 0);
             debug_assert!(index < len);
@@ -247,7 +733,7 @@ impl<T> RawDataPtr<T> {
             // valid to the data at last, and the data at last invalid (even if
             // it is still bitwise identical to the data at index).
             let last = len - 1;
-            let array_ptr = self.0.as_ptr();
+            let array_ptr = self.ptr.as_ptr();
             let result = ptr::read(array_ptr.add(index)).assume_init();
             ptr::copy(array_ptr.add(last), array_ptr.add(index), 1);
             *array_ptr.add(last) = MaybeUninit::uninit(); // Hint for Miri
@@ -257,6 +743,10 @@ impl<T> RawDataPtr<T> {
 
     /// Drops all elements in the range `0..len`.
     ///
+    /// If the destructor of any element panics, the remaining elements in the range
+    /// are still dropped before the panic continues to unwind. Either way, all of the
+    /// elements in the range `0..len` must be treated as invalid afterwards.
+    ///
     /// # Safety
     ///
     /// It is up to the caller to guarantee the following:
@@ -264,28 +754,396 @@ impl<T> RawDataPtr<T> {
     /// - `len <= N`
     #[inline(always)]
     pub unsafe fn drop_to(&mut self, len: usize) {
+        self.check.on_drop(len);
+
         unsafe {
-            for i in 0..len {
-                let i_ptr = self.0.as_ptr().add(i);
-                // SAFETY: The caller guarantees this element is valid.
-                ptr::drop_in_place(i_ptr as *mut T);
-                ptr::write(i_ptr, MaybeUninit::uninit()); // Hint for Miri
-            }
+            // SAFETY: The caller guarantees these elements are valid. Dropping them
+            // as a slice keeps dropping the remaining elements if one of them panics.
+            let elements = ptr::slice_from_raw_parts_mut(self.ptr.as_ptr() as *mut T, len);
+            ptr::drop_in_place(elements);
         };
     }
+
+    /// Checks that this pointer is the base of an allocation of exactly `capacity`
+    /// elements, of which exactly the elements in the range `0..len` are valid.
+    ///
+    /// This has no effect unless the `checked` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// With the `checked` feature enabled, this will panic if the invariants don't hold.
+    #[inline(always)]
+    pub fn validate(&self, capacity: usize, len: usize) {
+        self.check.validate(capacity, len);
+    }
+}
+
+/// A set of valid elements that is dropped when this guard is dropped.
+///
+/// Used for multi-column drops, so that a panicking destructor in one column
+/// still drops the elements in every other column (and never drops any twice).
+pub(crate) struct DropGuard<T> {
+    data: RawDataPtr<T>,
+    len: usize,
+}
+
+impl<T> DropGuard<T> {
+    /// Creates a guard that drops the elements in the range `0..len` of `data`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - All elements in the range `0..len` are valid
+    /// - Those elements will not be accessed or dropped by anything else
+    #[inline(always)]
+    pub(crate) unsafe fn new(data: &RawDataPtr<T>, len: usize) -> Self {
+        Self {
+            data: data.shallow_copy(),
+            len,
+        }
+    }
+}
+
+impl<T> Drop for DropGuard<T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: The creator of this guard guarantees that the elements are
+            // valid and that this guard has exclusive ownership of them.
+            self.data.drop_to(self.len);
+        }
+    }
+}
+
+/// An allocated data block that is deallocated on drop unless committed.
+///
+/// Used for transactional growth across multiple columns, where every new block
+/// must be allocated before any of the old blocks can be released, and to make
+/// sure that blocks are still released if dropping their elements panics.
+#[cfg(feature = "alloc")]
+pub(crate) struct AllocGuard<'a, T, A: Allocator> {
+    data: RawDataPtr<T>,
+    capacity: usize,
+    align: usize,
+    alloc: &'a A,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator> AllocGuard<'a, T, A> {
+    #[inline]
+    pub(crate) fn try_new(
+        capacity: usize,
+        align: usize,
+        alloc: &'a A,
+    ) -> Result<Self, TryReserveError> {
+        Ok(Self {
+            data: RawDataPtr::try_with_capacity_in(capacity, align, alloc)?,
+            capacity,
+            align,
+            alloc,
+        })
+    }
+
+    /// Allocates a new, zero-filled data block, as in [`try_new`](Self::try_new).
+    #[inline]
+    pub(crate) fn try_new_zeroed(
+        capacity: usize,
+        align: usize,
+        alloc: &'a A,
+    ) -> Result<Self, TryReserveError> {
+        Ok(Self {
+            data: RawDataPtr::try_with_capacity_zeroed_in(capacity, align, alloc)?,
+            capacity,
+            align,
+            alloc,
+        })
+    }
+
+    /// Creates a guard that deallocates the given existing data block.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `data` has exactly `capacity` elements allocated (may be 0) from `alloc`
+    ///   with the given `align`
+    /// - The block will not be accessed or deallocated by anything else afterwards
+    #[inline(always)]
+    pub(crate) unsafe fn new(
+        data: &RawDataPtr<T>,
+        capacity: usize,
+        align: usize,
+        alloc: &'a A,
+    ) -> Self {
+        Self {
+            data: data.shallow_copy(),
+            capacity,
+            align,
+            alloc,
+        }
+    }
+
+    /// Keeps this block, handing it over to the caller.
+    #[inline(always)]
+    pub(crate) fn into_data(self) -> RawDataPtr<T> {
+        let new = mem::ManuallyDrop::new(self);
+
+        unsafe {
+            // SAFETY: The guard is never used or dropped again, so we can move out.
+            ptr::read(&new.data)
+        }
+    }
+
+    /// Moves the first `len` elements of `old` into this block, deallocates `old`,
+    /// and replaces it with this block.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `old` has exactly `old_capacity` elements allocated (may be 0) from `self.alloc`
+    ///   with the given `old_align`
+    /// - All elements in the range `0..len` of `old` are valid
+    /// - `len <= old_capacity` and `len <= self.capacity`
+    #[inline]
+    pub(crate) unsafe fn commit(
+        self,
+        old: &mut RawDataPtr<T>,
+        old_capacity: usize,
+        old_align: usize,
+        len: usize,
+    ) {
+        debug_assert!(len <= old_capacity);
+        debug_assert!(len <= self.capacity);
+
+        let new = mem::ManuallyDrop::new(self);
+
+        unsafe {
+            // SAFETY: The two blocks are distinct allocations (or dangling for ZSTs),
+            // and the caller guarantees that the first len elements of old are valid.
+            ptr::copy_nonoverlapping(old.ptr.as_ptr(), new.data.ptr.as_ptr(), len);
+            // SAFETY: The caller guarantees that old is allocated to old_capacity.
+            old.dealloc_in(old_capacity, old_align, new.alloc);
+            // SAFETY: The guard is never used or dropped again, so we can move out.
+            *old = ptr::read(&new.data);
+        }
+
+        old.mark_init(0, len);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator> Drop for AllocGuard<'_, T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: This block is allocated with exactly self.capacity elements.
+            self.data.dealloc_in(self.capacity, self.align, self.alloc);
+        }
+    }
+}
+
+/// A data block resized in place, which is resized back to its old capacity on drop
+/// unless committed.
+///
+/// Used for transactional resizing across multiple columns, where the allocator can
+/// often resize each block without moving it, but every column resized so far must
+/// be restored if a later one fails.
+#[cfg(feature = "alloc")]
+pub(crate) struct ResizeGuard<'a, T, A: Allocator> {
+    data: &'a mut RawDataPtr<T>,
+    old_capacity: usize,
+    capacity: usize,
+    align: usize,
+    alloc: &'a A,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, A: Allocator> ResizeGuard<'a, T, A> {
+    /// Resizes `data` from `old_capacity` to `capacity` elements, with every new
+    /// element zero-filled if `zeroed` is set.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `data` has exactly `old_capacity` elements allocated (may be 0) from `alloc`
+    ///   with the given `align`
+    /// - No valid elements remain in the range `capacity..old_capacity`
+    #[inline]
+    pub(crate) unsafe fn try_new(
+        data: &'a mut RawDataPtr<T>,
+        old_capacity: usize,
+        capacity: usize,
+        align: usize,
+        alloc: &'a A,
+        zeroed: bool,
+    ) -> Result<Self, TryReserveError> {
+        unsafe {
+            // SAFETY: The caller upholds the guarantees required by each operation.
+            match (capacity < old_capacity, zeroed) {
+                (true, _) => data.try_shrink_in(old_capacity, capacity, align, alloc)?,
+                (false, true) => data.try_grow_zeroed_in(old_capacity, capacity, align, alloc)?,
+                (false, false) => data.try_grow_in(old_capacity, capacity, align, alloc)?,
+            }
+        }
+
+        Ok(Self {
+            data,
+            old_capacity,
+            capacity,
+            align,
+            alloc,
+        })
+    }
+
+    /// Keeps the new capacity.
+    #[inline(always)]
+    pub(crate) fn commit(self) {
+        mem::forget(self);
+    }
 }
 
+#[cfg(feature = "alloc")]
+impl<T, A: Allocator> Drop for ResizeGuard<'_, T, A> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            // SAFETY: The block was resized to self.capacity, and any new elements past
+            // self.old_capacity were never initialized. Restoring the old capacity is
+            // handled like any other allocation failure if the allocator can't do it.
+            match self.capacity < self.old_capacity {
+                true => self.data.grow_in(self.capacity, self.old_capacity, self.align, self.alloc),
+                false => self.data.shrink_in(self.capacity, self.old_capacity, self.align, self.alloc),
+            }
+        }
+    }
+}
+
+/// A type-erased counterpart of [`RawDataPtr`], for columns whose element type is
+/// only known at runtime through its [`Layout`].
+///
+/// The element layout is not stored; the same layout must be passed to every
+/// operation on the data block. Unlike [`RawDataPtr`], this pointer is not tracked
+/// by the `checked` feature.
+pub struct RawBytePtr {
+    ptr: NonNull<u8>,
+}
+
+impl RawBytePtr {
+    /// Creates a dangling pointer, suitably aligned for elements of layout `elem`.
+    #[inline(always)]
+    pub fn dangling(elem: Layout) -> Self {
+        // SAFETY: An alignment is never zero.
+        let ptr = unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(elem.align())) };
+        Self { ptr }
+    }
+
+    /// Allocates a new data array with room for `capacity` elements of layout `elem`,
+    /// if any, from `alloc`.
+    ///
+    /// If `elem` is zero-sized, or the given capacity is 0, this will not allocate.
+    ///
+    /// Returns an error if there is not enough memory to perform the new allocation,
+    /// or if the resulting allocation size is greater than `isize::MAX`.
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn try_with_capacity_in<A: Allocator>(
+        elem: Layout,
+        capacity: usize,
+        alloc: &A,
+    ) -> Result<Self, TryReserveError> {
+        if (elem.size() == 0) || (capacity == 0) {
+            return Ok(Self::dangling(elem));
+        }
+
+        let layout = try_new_byte_layout(elem, capacity)?;
+        debug_assert!(layout.size() > 0);
+
+        let ptr = resolve_ptr(alloc.allocate(layout), layout)?;
+        Ok(Self { ptr })
+    }
+
+    /// Deallocates this array's data block, if any, back to `alloc`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - This block was allocated by `alloc` for exactly `capacity` elements of layout `elem`
+    /// - Every element has already been dropped or moved out
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub unsafe fn dealloc_in<A: Allocator>(&mut self, elem: Layout, capacity: usize, alloc: &A) {
+        if (elem.size() == 0) || (capacity == 0) {
+            return; // Nothing to deallocate
+        }
+
+        // SAFETY: This layout was already valid when the block was allocated.
+        let layout = try_new_byte_layout(elem, capacity).unwrap();
+
+        unsafe {
+            // SAFETY: The caller guarantees that this block is allocated to capacity.
+            Tracker::poison(self.ptr.as_ptr(), layout.size());
+            alloc.deallocate(self.ptr, layout);
+        }
+
+        self.ptr = Self::dangling(elem).ptr;
+    }
+
+    /// Returns the raw pointer for this storage, which may be dangling or uninitialized.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns a pointer to the element at `index`, for elements of size `size`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `index` is within the capacity of
+    /// this block (or that the elements are zero-sized).
+    #[inline(always)]
+    pub unsafe fn at(&self, size: usize, index: usize) -> *mut u8 {
+        // SAFETY: The caller guarantees that the offset stays within the block.
+        unsafe { self.ptr.as_ptr().add(size * index) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn try_new_byte_layout(elem: Layout, capacity: usize) -> Result<Layout, TryReserveError> {
+    // Layout::from_size_align already rejects any size greater than isize::MAX.
+    elem.pad_to_align()
+        .size()
+        .checked_mul(capacity)
+        .and_then(|size| Layout::from_size_align(size, elem.align()).ok())
+        .ok_or(TryReserveError::CapacityOverflow)
+}
+
+#[cfg(feature = "alloc")]
 #[inline(always)]
-fn new_layout<T>(capacity: usize) -> Layout {
-    let layout = Layout::array::<T>(capacity).unwrap();
-    assert!(layout.size() <= isize::MAX as usize, "allocation too large");
-    layout
+fn try_new_layout<T>(capacity: usize, align: usize) -> Result<Layout, TryReserveError> {
+    // Layout::array and Layout::align_to already reject any size greater than isize::MAX.
+    Layout::array::<T>(capacity)
+        .and_then(|layout| layout.align_to(align))
+        .map_err(|_| TryReserveError::CapacityOverflow)
 }
 
+#[cfg(feature = "alloc")]
 #[inline(always)]
-fn resolve_ptr<T>(ptr: *mut u8, layout: Layout) -> NonNull<T> {
-    match NonNull::new(ptr as *mut T) {
-        Some(p) => p,
-        None => alloc::handle_alloc_error(layout),
+fn resolve_ptr<T>(
+    ptr: Result<NonNull<[u8]>, AllocError>,
+    layout: Layout,
+) -> Result<NonNull<T>, TryReserveError> {
+    match ptr {
+        Ok(ptr) => Ok(ptr.cast()),
+        Err(AllocError) => Err(TryReserveError::AllocError { layout }),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cold]
+pub(crate) fn handle_reserve_error(err: TryReserveError) -> ! {
+    match err {
+        TryReserveError::CapacityOverflow => panic!("allocation too large"),
+        TryReserveError::CapacityLocked => panic!("reallocation while the capacity is locked"),
+        TryReserveError::AllocError { layout } => handle_alloc_error(layout),
     }
 }
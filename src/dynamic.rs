@@ -0,0 +1,647 @@
+//! Type-erased tables whose columns are described at runtime.
+//!
+//! A [`DynVecSoa`] stores one column per [`ColumnDesc`], each identified by the
+//! [`TypeId`] of its elements. This is the storage behind an archetype-based ECS:
+//! every distinct set of component types gets its own table, and rows move between
+//! tables with [`move_row_to`](DynVecSoa::move_row_to) as components are added or
+//! removed.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::any::TypeId;
+use core::mem;
+use core::ops::Range;
+use core::ptr::{self, NonNull};
+use core::slice;
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use crate::data::{handle_reserve_error, RawBytePtr};
+use crate::error::TryReserveError;
+use crate::growth::GrowthPolicy;
+
+/// A runtime description of a column's element type.
+#[derive(Clone, Copy, Debug)]
+pub struct ColumnDesc {
+    type_id: TypeId,
+    layout: Layout,
+    drop_fn: Option<unsafe fn(*mut u8)>,
+    clone_fn: Option<unsafe fn(*const u8, *mut u8)>,
+}
+
+impl ColumnDesc {
+    /// Describes a column holding elements of type `T`.
+    ///
+    /// The column can't be cloned; see [`of_clone`](Self::of_clone).
+    #[inline]
+    pub fn of<T: Send + Sync + 'static>() -> Self {
+        let drop_fn: Option<unsafe fn(*mut u8)> = match mem::needs_drop::<T>() {
+            true => Some(drop_erased::<T>),
+            false => None,
+        };
+
+        Self {
+            type_id: TypeId::of::<T>(),
+            layout: Layout::new::<T>(),
+            drop_fn,
+            clone_fn: None,
+        }
+    }
+
+    /// Describes a column holding elements of type `T`, which can be cloned.
+    #[inline]
+    pub fn of_clone<T: Clone + Send + Sync + 'static>() -> Self {
+        Self {
+            clone_fn: Some(clone_erased::<T>),
+            ..Self::of::<T>()
+        }
+    }
+
+    /// Describes a column from its raw parts.
+    ///
+    /// The layout is padded to a multiple of its alignment, like any Rust type's.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - `layout` is the layout of the type identified by `type_id`
+    /// - `drop_fn`, if any, drops a valid value of that type in place
+    /// - `clone_fn`, if any, writes a clone of the value behind its first argument
+    ///   into the uninitialized memory behind its second argument
+    /// - Values of the type can be sent and shared between threads
+    #[inline]
+    pub unsafe fn new(
+        type_id: TypeId,
+        layout: Layout,
+        drop_fn: Option<unsafe fn(*mut u8)>,
+        clone_fn: Option<unsafe fn(*const u8, *mut u8)>,
+    ) -> Self {
+        Self {
+            type_id,
+            layout: layout.pad_to_align(),
+            drop_fn,
+            clone_fn,
+        }
+    }
+
+    /// Returns the unique identifier of the element type.
+    #[inline(always)]
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns the layout of a single element.
+    #[inline(always)]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns whether elements need to be dropped.
+    #[inline(always)]
+    pub fn needs_drop(&self) -> bool {
+        self.drop_fn.is_some()
+    }
+
+    /// Returns whether elements can be cloned.
+    #[inline(always)]
+    pub fn is_clone(&self) -> bool {
+        self.clone_fn.is_some()
+    }
+}
+
+unsafe fn drop_erased<T>(ptr: *mut u8) {
+    // SAFETY: The caller guarantees that ptr points to a valid T.
+    unsafe { ptr::drop_in_place(ptr.cast::<T>()) }
+}
+
+unsafe fn clone_erased<T: Clone>(src: *const u8, dst: *mut u8) {
+    // SAFETY: The caller guarantees that src points to a valid T and dst is writable.
+    unsafe { dst.cast::<T>().write((*src.cast::<T>()).clone()) }
+}
+
+/// A growable table whose columns are described at runtime.
+///
+/// Each column holds elements of a distinct type, and is looked up by its
+/// [`TypeId`]. Rows are written and moved as raw bytes, so typed access goes
+/// through [`column`](Self::column) and [`column_mut`](Self::column_mut).
+pub struct DynVecSoa<A: Allocator = Global> {
+    columns: Vec<ColumnDesc>,
+    data: Vec<RawBytePtr>,
+    len: usize,
+    capacity: usize,
+    growth: GrowthPolicy,
+    alloc: A,
+}
+
+// SAFETY: Every column type is required to be Send and Sync by ColumnDesc.
+unsafe impl<A: Allocator + Send> Send for DynVecSoa<A> {}
+unsafe impl<A: Allocator + Sync> Sync for DynVecSoa<A> {}
+
+impl DynVecSoa {
+    /// Constructs a new, empty table with the given columns.
+    ///
+    /// The table will not allocate until rows are pushed onto it.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if two columns have the same element type.
+    #[inline]
+    pub fn new(columns: impl IntoIterator<Item = ColumnDesc>) -> Self {
+        Self::new_in(columns, Global)
+    }
+}
+
+impl<A: Allocator> DynVecSoa<A> {
+    /// Constructs a new, empty table with the given columns, allocating from `alloc`.
+    ///
+    /// See [`new`](DynVecSoa::new) for details.
+    #[inline]
+    pub fn new_in(columns: impl IntoIterator<Item = ColumnDesc>, alloc: A) -> Self {
+        let columns: Vec<ColumnDesc> = columns.into_iter().collect();
+
+        for (index, column) in columns.iter().enumerate() {
+            assert!(
+                columns[..index].iter().all(|c| c.type_id != column.type_id),
+                "DynVecSoa::new: duplicate column at index {index}"
+            );
+        }
+
+        let data = columns
+            .iter()
+            .map(|c| RawBytePtr::dangling(c.layout))
+            .collect();
+
+        Self {
+            columns,
+            data,
+            len: 0,
+            capacity: 0,
+            growth: GrowthPolicy::default(),
+            alloc,
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    #[inline(always)]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the descriptions of this table's columns, in order.
+    #[inline(always)]
+    pub fn columns(&self) -> &[ColumnDesc] {
+        &self.columns
+    }
+
+    /// Returns the position of the column holding elements of the given type, if any.
+    #[inline]
+    pub fn column_index(&self, type_id: TypeId) -> Option<usize> {
+        self.columns.iter().position(|c| c.type_id == type_id)
+    }
+
+    /// Returns whether this table has a column holding elements of the given type.
+    #[inline]
+    pub fn has_column(&self, type_id: TypeId) -> bool {
+        self.column_index(type_id).is_some()
+    }
+
+    /// Returns the number of rows in this table.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if this table has no rows.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of rows this table can hold without reallocating.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the policy used to compute new capacities when growing.
+    #[inline(always)]
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth
+    }
+
+    /// Sets the policy used to compute new capacities when growing.
+    #[inline(always)]
+    pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+        self.growth = growth;
+    }
+
+    /// Returns the column holding elements of type `T` as a slice, if any.
+    #[inline]
+    pub fn column<T: 'static>(&self) -> Option<&[T]> {
+        let index = self.column_index(TypeId::of::<T>())?;
+
+        unsafe {
+            // SAFETY: The column holds len valid elements of type T.
+            Some(slice::from_raw_parts(
+                self.data[index].as_ptr().cast::<T>(),
+                self.len,
+            ))
+        }
+    }
+
+    /// Returns the column holding elements of type `T` as a mutable slice, if any.
+    #[inline]
+    pub fn column_mut<T: 'static>(&mut self) -> Option<&mut [T]> {
+        let index = self.column_index(TypeId::of::<T>())?;
+
+        unsafe {
+            // SAFETY: The column holds len valid elements of type T, and we hold &mut self.
+            Some(slice::from_raw_parts_mut(
+                self.data[index].as_ptr().cast::<T>(),
+                self.len,
+            ))
+        }
+    }
+
+    /// Returns a raw pointer to the element at `row` of the column at `column`.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `column` or `row` is out of bounds.
+    #[inline]
+    pub fn get_ptr(&self, column: usize, row: usize) -> NonNull<u8> {
+        assert!(row < self.len, "row index out of bounds");
+
+        unsafe {
+            // SAFETY: The row is in bounds, so the pointer is within the block.
+            NonNull::new_unchecked(self.element(column, row))
+        }
+    }
+
+    /// Reserves capacity for at least `len` more rows.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if there is not enough memory to perform the new
+    /// allocation, or if the resulting allocation size is greater than `isize::MAX`.
+    #[inline]
+    pub fn reserve(&mut self, len: usize) {
+        if let Err(err) = self.try_reserve(len) {
+            handle_reserve_error(err);
+        }
+    }
+
+    /// Tries to reserve capacity for at least `len` more rows.
+    ///
+    /// Growth is transactional across columns: if any column fails to allocate,
+    /// every column is left with its original allocation and contents.
+    #[inline]
+    pub fn try_reserve(&mut self, len: usize) -> Result<(), TryReserveError> {
+        let required = self
+            .len
+            .checked_add(len)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
+        if required > self.capacity {
+            let elem_size = self.columns.iter().map(|c| c.layout.size()).max();
+            let new_capacity =
+                self.growth
+                    .next_capacity(self.capacity, required, elem_size.unwrap_or(0));
+            self.try_relocate(new_capacity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves every column into a newly allocated block of `new_capacity` rows.
+    fn try_relocate(&mut self, new_capacity: usize) -> Result<(), TryReserveError> {
+        debug_assert!(new_capacity >= self.len);
+
+        let mut blocks = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            match RawBytePtr::try_with_capacity_in(column.layout, new_capacity, &self.alloc) {
+                Ok(block) => blocks.push(block),
+                Err(err) => {
+                    for (column, block) in self.columns.iter().zip(&mut blocks) {
+                        unsafe {
+                            // SAFETY: This block was just allocated and holds nothing.
+                            block.dealloc_in(column.layout, new_capacity, &self.alloc);
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        for ((column, old), new) in self.columns.iter().zip(&mut self.data).zip(&blocks) {
+            unsafe {
+                // SAFETY: Both blocks hold at least len elements and are distinct.
+                let size = column.layout.size();
+                ptr::copy_nonoverlapping(old.as_ptr(), new.as_ptr(), self.len * size);
+                // SAFETY: Every element was moved into the new block.
+                old.dealloc_in(column.layout, self.capacity, &self.alloc);
+            }
+        }
+
+        self.data = blocks;
+        self.capacity = new_capacity;
+        Ok(())
+    }
+
+    /// Pushes a new row onto the end of this table, returning its index.
+    ///
+    /// Each element is moved by copying its bytes from the matching pointer in
+    /// `row`, which must hold one pointer per column, in column order.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the following:
+    /// - Each pointer points to a valid value of its column's element type
+    /// - Each value is treated as moved out, and is never used or dropped again
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `row` does not have one pointer per column, or if
+    /// the table needs to grow and the allocation fails.
+    #[inline]
+    pub unsafe fn push_row(&mut self, row: &[NonNull<u8>]) -> usize {
+        assert_eq!(row.len(), self.columns.len(), "wrong number of columns");
+        self.reserve(1);
+
+        let index = self.len;
+        for (column, src) in row.iter().enumerate() {
+            unsafe {
+                // SAFETY: We reserved room for this row, and the caller guarantees that
+                // src points to a valid value of this column's type.
+                let size = self.columns[column].layout.size();
+                ptr::copy_nonoverlapping(src.as_ptr(), self.element(column, index), size);
+            }
+        }
+
+        self.len += 1;
+        index
+    }
+
+    /// Removes the row at `index`, dropping its elements.
+    ///
+    /// The removed row is replaced by the last row of this table.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `index` is out of bounds.
+    #[inline]
+    pub fn swap_remove(&mut self, index: usize) {
+        self.take_row(index);
+
+        unsafe {
+            // SAFETY: The removed row now sits just past the end, where nothing else
+            // can access it.
+            drop_rows(&self.columns, &self.data, self.len..self.len + 1, 0);
+        }
+    }
+
+    /// Moves the row at `index` onto the end of `other`, returning its new index.
+    ///
+    /// Elements of columns that both tables share are moved as bytes, and elements
+    /// of columns that `other` lacks are dropped. The row is removed from this table
+    /// as with [`swap_remove`](Self::swap_remove).
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `index` is out of bounds, or if `other` has a
+    /// column that this table lacks; see [`move_row_to_with`](Self::move_row_to_with).
+    #[inline]
+    pub fn move_row_to<B: Allocator>(&mut self, index: usize, other: &mut DynVecSoa<B>) -> usize {
+        for column in &other.columns {
+            assert!(
+                self.has_column(column.type_id),
+                "DynVecSoa::move_row_to: the destination has a column missing from the source"
+            );
+        }
+
+        unsafe {
+            // SAFETY: Every column of other is shared, so init is never called.
+            self.move_row_to_with(index, other, |_, _| unreachable!())
+        }
+    }
+
+    /// Moves the row at `index` onto the end of `other`, returning its new index.
+    ///
+    /// This works like [`move_row_to`](Self::move_row_to), except that `init` is called
+    /// with the column index and element pointer of every column of `other` that this
+    /// table lacks, to write that column's new element.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `init` writes a valid value of the
+    /// column's element type to the given pointer.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `index` is out of bounds. If `init` or a dropped
+    /// element panics, the row is removed from this table and never added to `other`,
+    /// and its elements may be leaked.
+    pub unsafe fn move_row_to_with<B: Allocator>(
+        &mut self,
+        index: usize,
+        other: &mut DynVecSoa<B>,
+        mut init: impl FnMut(usize, NonNull<u8>),
+    ) -> usize {
+        assert!(index < self.len, "row index out of bounds");
+        other.reserve(1);
+
+        let dst = other.len;
+        for (column, desc) in other.columns.iter().enumerate() {
+            if let Some(src) = self.column_index(desc.type_id) {
+                unsafe {
+                    // SAFETY: Both rows are in bounds, and the columns have the same type.
+                    ptr::copy_nonoverlapping(
+                        self.element(src, index),
+                        other.element(column, dst),
+                        desc.layout.size(),
+                    );
+                }
+            }
+        }
+
+        // Nothing past this point can touch the elements we just moved out.
+        self.take_row(index);
+        let last = self.len;
+
+        for (column, desc) in self.columns.iter().enumerate() {
+            if let (Some(drop_fn), false) = (desc.drop_fn, other.has_column(desc.type_id)) {
+                unsafe {
+                    // SAFETY: This element was not moved, and sits past the end.
+                    drop_fn(self.data[column].at(desc.layout.size(), last));
+                }
+            }
+        }
+
+        for (column, desc) in other.columns.iter().enumerate() {
+            if !self.has_column(desc.type_id) {
+                unsafe {
+                    // SAFETY: We reserved room for this row in other.
+                    init(column, NonNull::new_unchecked(other.element(column, dst)));
+                }
+            }
+        }
+
+        other.len += 1;
+        dst
+    }
+
+    /// Clears this table, dropping every row. This does not change its capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        // Set the length first, so that a panicking destructor can never cause
+        // any element to be observed or dropped again after this call.
+        let len = mem::replace(&mut self.len, 0);
+
+        unsafe {
+            // SAFETY: Every element in 0..len is valid and no longer reachable.
+            drop_rows(&self.columns, &self.data, 0..len, 0);
+        }
+    }
+
+    /// Returns a copy of this table with every row cloned, or `None` if any column
+    /// can't be cloned.
+    pub fn try_clone(&self) -> Option<Self>
+    where
+        A: Clone,
+    {
+        if !self.columns.iter().all(ColumnDesc::is_clone) {
+            return None;
+        }
+
+        let mut clone = Self::new_in(self.columns.iter().copied(), self.alloc.clone());
+        clone.growth = self.growth;
+        clone.reserve(self.len);
+
+        for row in 0..self.len {
+            for (column, desc) in self.columns.iter().enumerate() {
+                // If a clone panics, the columns already cloned for this row are leaked.
+                let clone_fn = desc.clone_fn.unwrap();
+                unsafe {
+                    // SAFETY: The source row is valid, and we reserved the target row.
+                    clone_fn(self.element(column, row), clone.element(column, row));
+                }
+            }
+
+            clone.len += 1;
+        }
+
+        Some(clone)
+    }
+
+    /// Swaps the row at `index` with the last row and shrinks the length by one, so
+    /// that the removed row sits just past the end, logically uninitialized.
+    #[inline]
+    fn take_row(&mut self, index: usize) {
+        assert!(index < self.len, "row index out of bounds");
+
+        let last = self.len - 1;
+        if index != last {
+            for (column, desc) in self.columns.iter().enumerate() {
+                unsafe {
+                    // SAFETY: Both rows are in bounds and distinct.
+                    let size = desc.layout.size();
+                    let data = &self.data[column];
+                    ptr::swap_nonoverlapping(data.at(size, index), data.at(size, last), size);
+                }
+            }
+        }
+
+        self.len = last;
+    }
+
+    /// Returns a pointer to the element at `row` of the column at `column`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `row` is within the capacity.
+    #[inline(always)]
+    unsafe fn element(&self, column: usize, row: usize) -> *mut u8 {
+        // SAFETY: The caller guarantees that the row is within the capacity.
+        unsafe { self.data[column].at(self.columns[column].layout.size(), row) }
+    }
+}
+
+impl<A: Allocator> Drop for DynVecSoa<A> {
+    fn drop(&mut self) {
+        // Deallocate even if a destructor panics.
+        struct Dealloc<'a, A: Allocator>(&'a mut DynVecSoa<A>);
+
+        impl<A: Allocator> Drop for Dealloc<'_, A> {
+            fn drop(&mut self) {
+                let table = &mut *self.0;
+                for (column, data) in table.columns.iter().zip(&mut table.data) {
+                    unsafe {
+                        // SAFETY: Every block is allocated by alloc to capacity.
+                        data.dealloc_in(column.layout, table.capacity, &table.alloc);
+                    }
+                }
+            }
+        }
+
+        Dealloc(self).0.clear();
+    }
+}
+
+/// Drops the elements in `rows` of every column, starting at column `first`.
+///
+/// If a destructor panics, the remaining elements are still dropped while unwinding.
+///
+/// # Safety
+///
+/// It is up to the caller to guarantee that every element in `rows` of these columns
+/// is valid and never used or dropped again.
+unsafe fn drop_rows(columns: &[ColumnDesc], data: &[RawBytePtr], rows: Range<usize>, first: usize) {
+    struct Resume<'a> {
+        columns: &'a [ColumnDesc],
+        data: &'a [RawBytePtr],
+        rows: Range<usize>,
+        column: usize,
+        next: usize,
+    }
+
+    impl Drop for Resume<'_> {
+        fn drop(&mut self) {
+            let (columns, column) = (self.columns, self.column);
+            unsafe {
+                // SAFETY: Our caller guarantees that the remaining elements are valid.
+                // Finish the current column, then move on to the next ones.
+                drop_rows(
+                    &columns[..=column],
+                    self.data,
+                    self.next..self.rows.end,
+                    column,
+                );
+                drop_rows(columns, self.data, self.rows.clone(), column + 1);
+            }
+        }
+    }
+
+    for column in first..columns.len() {
+        let Some(drop_fn) = columns[column].drop_fn else {
+            continue;
+        };
+
+        let size = columns[column].layout.size();
+        for row in rows.clone() {
+            let resume = Resume {
+                columns,
+                data,
+                rows: rows.clone(),
+                column,
+                next: row + 1,
+            };
+
+            unsafe {
+                // SAFETY: The caller guarantees that this element is valid.
+                drop_fn(data[column].at(size, row));
+            }
+
+            mem::forget(resume);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! An object-safe trait for managing vectors of any arity through `dyn`.
+//!
+//! [`AnySoa`] covers the operations that don't depend on a vector's element types,
+//! so a single registry (such as a `Vec<Box<dyn AnySoa>>`) can clear, trim and
+//! report on tables of different arities, and downcast back to a concrete type when
+//! it needs to.
+
+use core::any::Any;
+
+use allocator_api2::alloc::Allocator;
+use seq_macro::seq;
+
+use crate::vec::*;
+
+/// Type-erased operations shared by every `VecSoaN`.
+pub trait AnySoa: Any {
+    /// Returns the number of rows.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no rows.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of rows that can be held without reallocating.
+    fn capacity(&self) -> usize;
+
+    /// Returns the total number of bytes allocated across every column.
+    fn heap_bytes(&self) -> usize;
+
+    /// Removes every row, dropping its elements. This does not change the capacity.
+    fn clear(&mut self);
+
+    /// Removes the row at `index` and drops it, replacing it with the last row.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `index` is out of bounds.
+    fn swap_remove_drop(&mut self, index: usize);
+
+    /// Shortens the vector to `len` rows, dropping the rest.
+    fn truncate(&mut self, len: usize);
+
+    /// Reserves capacity for at least `len` more rows.
+    fn reserve(&mut self, len: usize);
+
+    /// Shrinks the capacity as close as possible to the number of rows.
+    fn shrink_to_fit(&mut self);
+
+    /// Returns this vector as [`Any`], to downcast it to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns this vector as mutable [`Any`], to downcast it to its concrete type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl dyn AnySoa {
+    /// Returns a reference to the concrete vector, if it is of type `V`.
+    #[inline]
+    pub fn downcast_ref<V: AnySoa>(&self) -> Option<&V> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to the concrete vector, if it is of type `V`.
+    #[inline]
+    pub fn downcast_mut<V: AnySoa>(&mut self) -> Option<&mut V> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+macro_rules! impl_n {
+    ($vec:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I: 'static,)* A: Allocator + 'static> AnySoa for $vec<#(T~I,)* A> {
+            #[inline]
+            fn len(&self) -> usize {
+                $vec::len(self)
+            }
+
+            #[inline]
+            fn capacity(&self) -> usize {
+                $vec::capacity(self)
+            }
+
+            #[inline]
+            fn heap_bytes(&self) -> usize {
+                $vec::heap_bytes(self)
+            }
+
+            #[inline]
+            fn clear(&mut self) {
+                $vec::clear(self);
+            }
+
+            #[inline]
+            fn swap_remove_drop(&mut self, index: usize) {
+                drop($vec::swap_remove(self, index));
+            }
+
+            #[inline]
+            fn truncate(&mut self, len: usize) {
+                $vec::truncate(self, len);
+            }
+
+            #[inline]
+            fn reserve(&mut self, len: usize) {
+                $vec::reserve(self, len);
+            }
+
+            #[inline]
+            fn shrink_to_fit(&mut self) {
+                $vec::shrink_to_fit(self);
+            }
+
+            #[inline]
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            #[inline]
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(VecSoa~N, N);
+});
@@ -0,0 +1,112 @@
+use core::alloc::Layout;
+use core::fmt;
+
+/// The error type for `try_reserve` methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The computed capacity exceeded the maximum (usually `isize::MAX` bytes).
+    CapacityOverflow,
+    /// The memory allocator returned an error.
+    AllocError {
+        /// The layout of the allocation request that failed.
+        layout: Layout,
+    },
+    /// The vector would have to reallocate while its capacity is locked. This is
+    /// only reported with debug assertions enabled.
+    CapacityLocked,
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")?;
+        match self {
+            TryReserveError::CapacityOverflow => {
+                f.write_str(" because the computed capacity exceeded the collection's maximum")
+            }
+            TryReserveError::AllocError { .. } => {
+                f.write_str(" because the memory allocator returned an error")
+            }
+            TryReserveError::CapacityLocked => {
+                f.write_str(" because the collection's capacity is locked")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryReserveError {}
+
+/// The error type for looking up columns by their element type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnError {
+    /// No column holds elements of the requested type.
+    Missing {
+        /// The name of the requested element type.
+        type_name: &'static str,
+    },
+    /// More than one column holds elements of the requested type.
+    Ambiguous {
+        /// The name of the requested element type.
+        type_name: &'static str,
+    },
+    /// A query borrows the same column mutably more than once, or both mutably
+    /// and immutably.
+    Aliased {
+        /// The name of the element type of the aliased column.
+        type_name: &'static str,
+    },
+}
+
+impl fmt::Display for ColumnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnError::Missing { type_name } => {
+                write!(f, "no column holds elements of type `{type_name}`")
+            }
+            ColumnError::Ambiguous { type_name } => {
+                write!(
+                    f,
+                    "more than one column holds elements of type `{type_name}`"
+                )
+            }
+            ColumnError::Aliased { type_name } => {
+                write!(f, "the column of type `{type_name}` is borrowed mutably while also borrowed elsewhere")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ColumnError {}
+
+/// The error type for runtime-checked column borrows that conflict with an
+/// existing borrow of the same column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorrowError {
+    /// The column is already borrowed mutably, so it can't be borrowed at all.
+    MutablyBorrowed {
+        /// The position of the column.
+        column: usize,
+    },
+    /// The column is already borrowed, so it can't be borrowed mutably.
+    Borrowed {
+        /// The position of the column.
+        column: usize,
+    },
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowError::MutablyBorrowed { column } => {
+                write!(f, "column {column} is already mutably borrowed")
+            }
+            BorrowError::Borrowed { column } => {
+                write!(f, "column {column} is already borrowed")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BorrowError {}
@@ -0,0 +1,53 @@
+/// Controls how much a vector's capacity grows when it runs out of room.
+///
+/// Regardless of the policy, the new capacity is always at least large enough
+/// to hold the requested number of elements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// Doubles the capacity on every growth. This is the default.
+    #[default]
+    Double,
+    /// Grows the capacity by half of its current value on every growth.
+    OneAndHalf,
+    /// Grows the capacity by a fixed number of elements on every growth.
+    Fixed(usize),
+    /// Doubles the capacity, then rounds it up so that the column with the largest
+    /// element type fills its final page of the given size in bytes.
+    PageRounded(usize),
+}
+
+impl GrowthPolicy {
+    /// Computes the new capacity for a vector that currently holds `capacity`
+    /// elements and needs room for at least `required` elements.
+    ///
+    /// The `elem_size` is the size in bytes of the largest element across all columns.
+    #[inline]
+    pub fn next_capacity(self, capacity: usize, required: usize, elem_size: usize) -> usize {
+        let grown = match self {
+            GrowthPolicy::Double => capacity.saturating_mul(2),
+            GrowthPolicy::OneAndHalf => capacity.saturating_add(capacity / 2),
+            GrowthPolicy::Fixed(step) => capacity.saturating_add(step),
+            GrowthPolicy::PageRounded(page) => {
+                let doubled = usize::max(capacity.saturating_mul(2), required);
+                round_to_page(doubled, elem_size, page)
+            }
+        };
+
+        usize::max(grown, required)
+    }
+}
+
+#[inline]
+fn round_to_page(capacity: usize, elem_size: usize, page: usize) -> usize {
+    if (elem_size == 0) || (page == 0) {
+        return capacity;
+    }
+
+    match capacity.checked_mul(elem_size) {
+        Some(bytes) => match bytes.checked_next_multiple_of(page) {
+            Some(rounded) => rounded / elem_size,
+            None => capacity,
+        },
+        None => capacity,
+    }
+}
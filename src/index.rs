@@ -7,13 +7,20 @@ pub mod index_range_to;
 pub mod index_range_to_inclusive;
 pub mod index_usize;
 
-use std::marker::PhantomData;
-use std::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use core::marker::PhantomData;
+use core::ops::{Bound, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
 
 use seq_macro::seq;
 
 use crate::slice::*;
 
+/// Indexing into a SoA slice by position or range.
+///
+/// # Safety
+///
+/// Implementors must guarantee that every safe method performs bounds checking before
+/// producing references, and that `get_unchecked` and `get_unchecked_mut` only ever
+/// produce references to valid elements when the caller upholds their requirements.
 pub unsafe trait IndexSoa<T> {
     type Output<'a>: 'a
     where
@@ -31,7 +38,17 @@ pub unsafe trait IndexSoa<T> {
     /// Get a mutable reference to the element at this index or range, if within bounds.
     fn get_mut<'a>(self, slice: &'a mut T) -> Option<Self::OutputMut<'a>>;
 
+    /// Get a reference to the element at this index or range, without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that this index or range is within bounds.
     unsafe fn get_unchecked<'a>(self, slice: &'a T) -> Self::Output<'a>;
+    /// Get a mutable reference to the element at this index or range, without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that this index or range is within bounds.
     unsafe fn get_unchecked_mut<'a>(self, slice: &'a mut T) -> Self::OutputMut<'a>;
 }
 
@@ -1,4 +1,4 @@
-use std::iter::Iterator;
+use core::iter::Iterator;
 
 use seq_macro::seq;
 
@@ -26,7 +26,7 @@ macro_rules! impl_n {
         impl<#(T~I: Sized,)*> $slice<#(T~I,)*> {
             /// Shallow-copies the slice, returning a slice that points to the same data.
             #[inline(always)]
-            pub fn iter(&self) -> $iter<#(T~I,)*> {
+            pub fn iter(&self) -> $iter<'_, #(T~I,)*> {
                 $iter {
                     index: 0,
                     slice: self.index(..),
@@ -35,7 +35,7 @@ macro_rules! impl_n {
 
             /// Shallow-copies the slice, returning a slice that points to the same data.
             #[inline(always)]
-            pub fn iter_mut(&mut self) -> $iter_mut<#(T~I,)*> {
+            pub fn iter_mut(&mut self) -> $iter_mut<'_, #(T~I,)*> {
                 $iter_mut {
                     index: 0,
                     slice: self.index_mut(..),
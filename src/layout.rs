@@ -0,0 +1,69 @@
+/// Controls the alignment and capacity padding of every column's allocation.
+///
+/// Each column's data block is aligned to at least `align` bytes, and the capacity
+/// is always rounded up to a multiple of `lanes` elements. For SIMD kernels, this
+/// means every column starts on a vector boundary and loops can process whole lanes
+/// past the end of the valid data without reading outside of the allocation.
+///
+/// Columns that haven't allocated (with zero capacity, or of a zero-sized type) keep
+/// a dangling pointer, which is only aligned to the column type's own alignment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnLayout {
+    align: usize,
+    lanes: usize,
+}
+
+impl ColumnLayout {
+    /// The natural alignment of each column type, with no capacity padding.
+    pub const NATURAL: ColumnLayout = ColumnLayout { align: 1, lanes: 1 };
+
+    /// Creates a layout that aligns every column to at least `align` bytes and pads
+    /// the capacity to a multiple of `lanes` elements.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `align` is not a power of two, or if `lanes` is 0.
+    #[inline]
+    pub const fn new(align: usize, lanes: usize) -> Self {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        assert!(lanes > 0, "lane count must be nonzero");
+        Self { align, lanes }
+    }
+
+    /// Creates a layout for SIMD vectors of `bytes` bytes: every column is aligned to
+    /// `bytes`, and the capacity is padded so that even a column of single-byte
+    /// elements fills a whole number of vectors.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `bytes` is not a power of two.
+    #[inline]
+    pub const fn simd(bytes: usize) -> Self {
+        Self::new(bytes, bytes)
+    }
+
+    /// Returns the minimum alignment, in bytes, of every column's allocation.
+    #[inline(always)]
+    pub const fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Returns the number of elements that the capacity is padded to a multiple of.
+    #[inline(always)]
+    pub const fn lanes(&self) -> usize {
+        self.lanes
+    }
+
+    /// Rounds `capacity` up to a multiple of the lane count, if it doesn't overflow.
+    #[inline]
+    pub const fn pad(&self, capacity: usize) -> Option<usize> {
+        capacity.checked_next_multiple_of(self.lanes)
+    }
+}
+
+impl Default for ColumnLayout {
+    #[inline]
+    fn default() -> Self {
+        Self::NATURAL
+    }
+}
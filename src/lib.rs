@@ -1,11 +1,86 @@
+#![no_std]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod array;
+pub mod atomic;
+#[cfg(feature = "alloc")]
+pub mod cell;
+mod checked;
+#[cfg(feature = "alloc")]
+pub mod concurrent;
+#[cfg(feature = "alloc")]
+pub mod cow;
 pub mod data;
+#[cfg(feature = "alloc")]
+pub mod dynamic;
+#[cfg(feature = "alloc")]
+pub mod erased;
+pub mod error;
+pub mod growth;
 pub mod index;
 pub mod iter;
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod packed;
+#[cfg(feature = "std")]
+pub mod par;
+pub mod query;
+#[cfg(feature = "std")]
+pub mod schedule;
+#[cfg(feature = "alloc")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod shared;
 pub mod slice;
+#[cfg(feature = "alloc")]
+pub mod small;
+#[cfg(feature = "alloc")]
+pub mod stats;
+#[cfg(feature = "alloc")]
 pub mod vec;
+pub mod zeroable;
+
+pub use allocator_api2::alloc::{AllocError, Allocator};
+#[cfg(feature = "alloc")]
+pub use allocator_api2::alloc::Global;
 
 pub mod prelude {
+    pub use super::array::*;
+    pub use super::atomic::*;
+    #[cfg(feature = "alloc")]
+    pub use super::cell::*;
+    #[cfg(feature = "alloc")]
+    pub use super::concurrent::*;
+    #[cfg(feature = "alloc")]
+    pub use super::cow::*;
+    #[cfg(feature = "alloc")]
+    pub use super::dynamic::*;
+    #[cfg(feature = "alloc")]
+    pub use super::erased::*;
+    pub use super::error::*;
+    pub use super::growth::*;
     pub use super::index::*;
+    pub use super::layout::*;
+    #[cfg(feature = "alloc")]
+    pub use super::packed::*;
+    pub use super::query::*;
+    #[cfg(feature = "std")]
+    pub use super::schedule::*;
+    #[cfg(feature = "alloc")]
+    pub use super::schema::*;
+    #[cfg(feature = "std")]
+    pub use super::shared::*;
     pub use super::slice::*;
+    #[cfg(feature = "alloc")]
+    pub use super::small::*;
+    #[cfg(feature = "alloc")]
+    pub use super::stats::*;
+    #[cfg(feature = "alloc")]
     pub use super::vec::*;
+    pub use super::zeroable::*;
 }
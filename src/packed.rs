@@ -0,0 +1,311 @@
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+
+use allocator_api2::alloc::{Allocator, Global};
+use seq_macro::seq;
+
+use crate::data::{handle_reserve_error, DropGuard, RawDataPtr};
+use crate::error::TryReserveError;
+use crate::growth::GrowthPolicy;
+use crate::layout::ColumnLayout;
+use crate::slice::*;
+#[cfg(feature = "stats")]
+use crate::stats::AllocStats;
+use crate::stats::StatsCounter;
+use crate::vec::impl_growable_n;
+use crate::zeroable::Zeroable;
+
+/// A single allocated block holding every column, released on drop.
+struct BlockGuard<'a, A: Allocator> {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    alloc: &'a A,
+}
+
+impl<A: Allocator> Drop for BlockGuard<'_, A> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.layout.size() > 0 {
+            unsafe {
+                // SAFETY: The creator of this guard guarantees that the block was
+                // allocated from alloc with this layout and isn't used afterwards.
+                self.alloc.deallocate(self.ptr, self.layout);
+            }
+        }
+    }
+}
+
+macro_rules! declare_packed_n {
+    (
+        $packed:ident,
+        $slice:ident,
+        $slice_ref:ident,
+        $slice_ref_mut:ident,
+        $n:literal
+    ) => { seq!(I in 0..$n {
+        /// A vector whose columns all share a single allocation.
+        ///
+        /// Each column lives at its own aligned offset within the block, so growing
+        /// performs one allocation and copies every column into its new offset.
+        /// Otherwise, it supports the same operations as a regular vector, including
+        /// growth policies, capacity locking, allocation statistics and column layouts.
+        /// It doesn't report per-column byte counts, since the block also holds the
+        /// padding between columns.
+        pub struct $packed<#(T~I: Sized,)* A: Allocator = Global> {
+            capacity: usize,
+            growth: GrowthPolicy,
+            layout: ColumnLayout,
+            capacity_locked: bool,
+            stats: StatsCounter,
+            block: NonNull<u8>,
+            slice: $slice<#(T~I,)*>,
+            alloc: A,
+        }
+
+        impl<#(T~I: Sized,)*> $packed<#(T~I,)*> {
+            #[inline]
+            pub fn new() -> Self {
+                Self::new_in(Global)
+            }
+
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self::with_capacity_in(capacity, Global)
+            }
+
+            /// Creates a new vector with room for the given number of rows.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+                Self::try_with_capacity_in(capacity, Global)
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> $packed<#(T~I,)* A> {
+            /// Creates a new, empty vector that allocates its block from `alloc`.
+            #[inline]
+            pub fn new_in(alloc: A) -> Self {
+                Self {
+                    capacity: 0,
+                    growth: GrowthPolicy::default(),
+                    layout: ColumnLayout::NATURAL,
+                    capacity_locked: false,
+                    stats: StatsCounter::default(),
+                    block: NonNull::dangling(),
+                    slice: $slice {
+                        len: 0,
+                        data: (#(RawDataPtr::with_capacity(0),)*),
+                    },
+                    alloc,
+                }
+            }
+
+            /// Creates a new vector with room for the given number of rows, allocating
+            /// its block from `alloc`.
+            #[inline]
+            pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+                match Self::try_with_capacity_in(capacity, alloc) {
+                    Ok(vec) => vec,
+                    Err(err) => handle_reserve_error(err),
+                }
+            }
+
+            /// Creates a new vector with room for the given number of rows, allocating
+            /// its block from `alloc`.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+                let mut vec = Self::new_in(alloc);
+                vec.try_reserve_exact(capacity)?;
+                Ok(vec)
+            }
+
+            /// Returns a reference to the allocator for this vector's block.
+            #[inline(always)]
+            pub fn allocator(&self) -> &A {
+                &self.alloc
+            }
+
+            #[inline]
+            pub fn as_slice(&self) -> $slice_ref<'_, #(T~I,)*> {
+                $slice_ref {
+                    slice: self.slice.shallow_copy(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> $slice_ref_mut<'_, #(T~I,)*> {
+                $slice_ref_mut {
+                    slice: self.slice.shallow_copy(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            /// Computes the layout of a block holding `capacity` rows with the given
+            /// column layout, along with the byte offset of each column within it.
+            #[inline]
+            fn block_layout(
+                capacity: usize,
+                layout: ColumnLayout,
+            ) -> Result<(Layout, [usize; $n]), TryReserveError> {
+                let mut block = Layout::new::<()>();
+                let mut offsets = [0; $n];
+
+                #(
+                    let (extended, offset) = Layout::array::<T~I>(capacity)
+                        .and_then(|column| column.align_to(layout.align()))
+                        .and_then(|column| block.extend(column))
+                        .map_err(|_| TryReserveError::CapacityOverflow)?;
+                    block = extended;
+                    offsets[I] = offset;
+                )*
+
+                Ok((block.pad_to_align(), offsets))
+            }
+
+            /// Moves every column into a new block with room for `new_capacity` rows, as
+            /// in [`try_relocate`](Self::try_relocate). The block can't be resized in place,
+            /// since every column after the first would have to move to a new offset.
+            #[inline]
+            fn try_resize(
+                &mut self,
+                new_capacity: usize,
+                zeroed: bool,
+            ) -> Result<(), TryReserveError> {
+                self.try_relocate(new_capacity, self.layout, zeroed)
+            }
+
+            /// Moves every column into a new block with room for `new_capacity` rows
+            /// with the given column layout, zero-filled if `zeroed` is set.
+            ///
+            /// The new block is allocated before the old one is touched, so an error
+            /// leaves the vector exactly as it was. Callers check the capacity lock
+            /// themselves, so that it is reported the right way.
+            fn try_relocate(
+                &mut self,
+                new_capacity: usize,
+                layout: ColumnLayout,
+                zeroed: bool,
+            ) -> Result<(), TryReserveError> {
+                debug_assert!(new_capacity >= self.slice.len);
+
+                let (block_layout, offsets) = Self::block_layout(new_capacity, layout)?;
+                let (old_block_layout, _) = Self::block_layout(self.capacity, self.layout)?;
+                let len = self.slice.len;
+
+                let block = if block_layout.size() == 0 {
+                    NonNull::dangling()
+                } else {
+                    let block = match zeroed {
+                        true => self.alloc.allocate_zeroed(block_layout),
+                        false => self.alloc.allocate(block_layout),
+                    };
+
+                    match block {
+                        Ok(block) => block.cast::<u8>(),
+                        Err(_) => return Err(TryReserveError::AllocError { layout: block_layout }),
+                    }
+                };
+
+                let _old_block = BlockGuard {
+                    ptr: mem::replace(&mut self.block, block),
+                    layout: old_block_layout,
+                    alloc: &self.alloc,
+                };
+
+                #(
+                    let mut column = if block_layout.size() == 0 {
+                        // Every column is empty or zero-sized, so use their own dangling pointers.
+                        RawDataPtr::<T~I>::with_capacity(new_capacity)
+                    } else {
+                        unsafe {
+                            // SAFETY: The offset is within the new block and aligned for the
+                            // column type, and this vector owns and releases the block.
+                            let ptr = NonNull::new_unchecked(block.as_ptr().add(offsets[I]));
+                            RawDataPtr::<T~I>::from_block(ptr, new_capacity)
+                        }
+                    };
+
+                    unsafe {
+                        // SAFETY: The old and new blocks are distinct allocations (or
+                        // dangling and zero-sized), and the data in 0..len is valid.
+                        ptr::copy_nonoverlapping(
+                            self.slice.data.I.raw_ptr().as_ptr(),
+                            column.raw_ptr().as_ptr(),
+                            len,
+                        );
+                    }
+
+                    column.mark_init(0, len);
+                    self.slice.data.I.release_block();
+                    self.slice.data.I = column;
+                )*
+
+                let moved = [#(len * mem::size_of::<T~I>(),)*].into_iter().sum();
+                self.stats.on_relocate_bytes(old_block_layout.size(), block_layout.size(), moved);
+
+                self.capacity = new_capacity;
+                self.layout = layout;
+                Ok(())
+            }
+        }
+
+        impl<#(T~I: Sized,)*> Default for $packed<#(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> Drop for $packed<#(T~I,)* A> {
+            #[inline]
+            fn drop(&mut self) {
+                let len = mem::replace(&mut self.slice.len, 0);
+
+                // Guards are dropped in reverse order, so the elements are dropped first
+                // and the block is always released, even if a destructor panics.
+                let _block = BlockGuard {
+                    ptr: self.block,
+                    layout: Self::block_layout(self.capacity, self.layout).unwrap().0,
+                    alloc: &self.alloc,
+                };
+                let _elements = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len.
+                    (#(DropGuard::new(&self.slice.data.I, len),)*)
+                };
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> Deref for $packed<#(T~I,)* A> {
+            type Target = $slice<#(T~I,)*>;
+
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.slice
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> DerefMut for $packed<#(T~I,)* A> {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.slice
+            }
+        }
+
+        unsafe impl<#(T~I: Send,)* A: Allocator + Send> Send for $packed<#(T~I,)* A> {}
+        unsafe impl<#(T~I: Sync,)* A: Allocator + Sync> Sync for $packed<#(T~I,)* A> {}
+    });}
+}
+
+seq!(N in 2..=12 {
+    declare_packed_n!(PackedVecSoa~N, SliceSoa~N, SliceSoaRef~N, SliceSoaRefMut~N, N);
+    impl_growable_n!(PackedVecSoa~N, N);
+});
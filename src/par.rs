@@ -0,0 +1,177 @@
+//! Scoped parallel processing of rows, using only `std` threads.
+//!
+//! The rows of a slice are split into chunks of a fixed length, and the chunks are
+//! split into one contiguous group per worker thread. Each worker runs on
+//! [`std::thread::scope`], with the calling thread acting as one of them, so no
+//! thread pool or extra dependency is needed.
+//!
+//! This module requires the `std` feature.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+
+use std::thread;
+
+use seq_macro::seq;
+
+use crate::slice::*;
+
+/// Resolves a requested thread count, where 0 means the available parallelism.
+fn resolve_threads(threads: usize) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        threads => threads,
+    }
+}
+
+/// Splits `len` rows into chunks of `chunk_len` rows, and those chunks into at most
+/// `threads` contiguous groups, returning the row range of each group.
+fn worker_ranges(len: usize, threads: usize, chunk_len: usize) -> Vec<Range<usize>> {
+    assert!(chunk_len > 0, "chunk length must be nonzero");
+
+    let chunks = len.div_ceil(chunk_len);
+    let workers = usize::min(resolve_threads(threads), chunks);
+
+    (0..workers)
+        .map(|worker| {
+            let first = worker * chunks / workers;
+            let last = (worker + 1) * chunks / workers;
+            (first * chunk_len)..usize::min(last * chunk_len, len)
+        })
+        .collect()
+}
+
+/// Returns the row range of each chunk of `chunk_len` rows within `rows`.
+fn chunk_ranges(rows: Range<usize>, chunk_len: usize) -> impl Iterator<Item = Range<usize>> {
+    let end = rows.end;
+    rows.step_by(chunk_len)
+        .map(move |start| start..usize::min(start + chunk_len, end))
+}
+
+macro_rules! impl_n {
+    ($slice:ident, $slice_ref:ident, $slice_ref_mut:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I: Send,)*> $slice<#(T~I,)*> {
+            /// Calls `f` on every chunk of `chunk_len` rows (the last may be shorter),
+            /// spreading the chunks across `threads` scoped threads.
+            ///
+            /// Each thread handles a contiguous group of chunks, and the calling thread
+            /// handles the last group. A `threads` of 0 uses
+            /// [`available_parallelism`](std::thread::available_parallelism).
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if `chunk_len` is 0. If `f` panics, the panic
+            /// is propagated once every thread has finished.
+            pub fn par_for_each_chunk<F>(&mut self, threads: usize, chunk_len: usize, f: F)
+            where
+                F: Fn($slice_ref_mut<'_, #(T~I,)*>) + Sync,
+            {
+                let mut ranges = worker_ranges(self.len, threads, chunk_len);
+                let Some(last) = ranges.pop() else {
+                    return;
+                };
+
+                let f = &f;
+                let work = move |base: $slice<#(T~I,)*>, rows: Range<usize>| {
+                    for chunk in chunk_ranges(rows, chunk_len) {
+                        f(unsafe {
+                            // SAFETY: The chunks of every worker are disjoint and within
+                            // bounds, and we hold self mutably until they are all done.
+                            $slice_ref_mut {
+                                slice: $slice {
+                                    len: chunk.end - chunk.start,
+                                    data: (#(base.data.I.offset(chunk.start),)*),
+                                },
+                                lifetime: PhantomData,
+                            }
+                        });
+                    }
+                };
+
+                thread::scope(|scope| {
+                    for rows in ranges {
+                        let base = self.shallow_copy();
+                        scope.spawn(move || work(base, rows));
+                    }
+
+                    work(self.shallow_copy(), last);
+                });
+            }
+        }
+
+        impl<#(T~I: Sync,)*> $slice<#(T~I,)*> {
+            /// Maps every chunk of `chunk_len` rows (the last may be shorter) to a value
+            /// with `map`, spreading the chunks across `threads` scoped threads, and
+            /// combines the values with `reduce`.
+            ///
+            /// Each thread folds its contiguous group of chunks in order, starting from
+            /// `identity()`, and the results of every thread are then folded in order.
+            /// This gives the same result as a sequential fold whenever `reduce` is
+            /// associative and `identity()` is its identity. A `threads` of 0 uses
+            /// [`available_parallelism`](std::thread::available_parallelism).
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if `chunk_len` is 0. If `map` or `reduce`
+            /// panics, the panic is propagated once every thread has finished.
+            pub fn par_map_reduce<R, M, ID, F>(
+                &self,
+                threads: usize,
+                chunk_len: usize,
+                map: M,
+                identity: ID,
+                reduce: F,
+            ) -> R
+            where
+                R: Send,
+                M: Fn($slice_ref<'_, #(T~I,)*>) -> R + Sync,
+                ID: Fn() -> R + Sync,
+                F: Fn(R, R) -> R + Sync,
+            {
+                let mut ranges = worker_ranges(self.len, threads, chunk_len);
+                let Some(last) = ranges.pop() else {
+                    return identity();
+                };
+
+                let (map, identity, reduce) = (&map, &identity, &reduce);
+                let work = move |rows: Range<usize>| {
+                    chunk_ranges(rows, chunk_len).fold(identity(), |acc, chunk| {
+                        reduce(acc, map(unsafe {
+                            // SAFETY: The chunk is within bounds, and we hold self for
+                            // reading until every worker is done.
+                            $slice_ref {
+                                slice: $slice {
+                                    len: chunk.end - chunk.start,
+                                    data: (#(self.data.I.offset(chunk.start),)*),
+                                },
+                                lifetime: PhantomData,
+                            }
+                        }))
+                    })
+                };
+
+                thread::scope(|scope| {
+                    let handles: Vec<_> = ranges
+                        .into_iter()
+                        .map(|rows| scope.spawn(move || work(rows)))
+                        .collect();
+
+                    let tail = work(last);
+                    let results = handles.into_iter().map(|handle| match handle.join() {
+                        Ok(result) => result,
+                        Err(panic) => std::panic::resume_unwind(panic),
+                    });
+
+                    let head = results.fold(identity(), reduce);
+                    reduce(head, tail)
+                })
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(SliceSoa~N, SliceSoaRef~N, SliceSoaRefMut~N, N);
+});
@@ -0,0 +1,304 @@
+//! Column lookup and row queries by element type.
+//!
+//! When every column of a slice holds a distinct type (as with ECS components),
+//! columns can be found by type instead of by position:
+//! `slice.column::<Velocity>()` returns the velocity column wherever it is, and
+//! `slice.query::<(&Position, &mut Velocity)>()` iterates over rows of just those
+//! columns. Queries of only `&T` terms can also run through a shared reference with
+//! `slice.query_ref::<(&Position, &Name)>()`. Lookups fail with a [`ColumnError`]
+//! when a type is missing, held by more than one column, or borrowed mutably more
+//! than once by the same query.
+
+use core::any::{self, TypeId};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::slice;
+
+use seq_macro::seq;
+
+use crate::error::ColumnError;
+use crate::slice::*;
+
+/// A single term of a query: `&T` to read the column of `T`, or `&mut T` to write it.
+///
+/// # Safety
+///
+/// `MUTABLE` must be `true` for any term that can write through the column.
+pub unsafe trait QueryTerm<'a>: Sized {
+    /// The element type of the column this term borrows.
+    type Elem: 'static;
+
+    /// Whether this term borrows its column mutably.
+    const MUTABLE: bool;
+
+    /// Borrows the element at `index`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `ptr` points to a column of at least
+    /// `index + 1` valid elements, borrowed for `'a` according to `MUTABLE`.
+    unsafe fn fetch(ptr: *mut Self::Elem, index: usize) -> Self;
+}
+
+unsafe impl<'a, T: 'static> QueryTerm<'a> for &'a T {
+    type Elem = T;
+    const MUTABLE: bool = false;
+
+    #[inline(always)]
+    unsafe fn fetch(ptr: *mut T, index: usize) -> Self {
+        // SAFETY: The caller guarantees that the element is valid and shared for 'a.
+        unsafe { &*ptr.add(index) }
+    }
+}
+
+unsafe impl<'a, T: 'static> QueryTerm<'a> for &'a mut T {
+    type Elem = T;
+    const MUTABLE: bool = true;
+
+    #[inline(always)]
+    unsafe fn fetch(ptr: *mut T, index: usize) -> Self {
+        // SAFETY: The caller guarantees that the element is valid and unique for 'a.
+        unsafe { &mut *ptr.add(index) }
+    }
+}
+
+/// A [`QueryTerm`] that only reads its column: `&T`.
+///
+/// # Safety
+///
+/// [`fetch`](QueryTerm::fetch) must never write through the column pointer.
+pub unsafe trait ReadTerm<'a>: QueryTerm<'a> {}
+
+// SAFETY: A shared reference can't be written through.
+unsafe impl<'a, T: 'static> ReadTerm<'a> for &'a T {}
+
+/// A tuple of [`QueryTerm`]s, such as `(&Position, &mut Velocity)`.
+///
+/// # Safety
+///
+/// [`query`](SliceSoa2::query) hands out the references from [`fetch`](Self::fetch)
+/// for every row at once, relying on [`resolve`](Self::resolve) to uphold the
+/// following:
+/// - Every returned pointer is one of the pointers in `columns`, and its element
+///   type matches the type id it was paired with
+/// - No column is returned for a mutable term while it is also returned for any
+///   other term
+pub unsafe trait SoaQuery<'a>: Sized {
+    /// The column pointers for each term.
+    type Ptrs: Copy;
+
+    /// Finds the column for each term among `columns`, checking that no column is
+    /// borrowed mutably by one term while borrowed by another.
+    fn resolve(columns: &[(TypeId, *mut u8)]) -> Result<Self::Ptrs, ColumnError>;
+
+    /// Borrows the row at `index` of every column.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `ptrs` came from [`resolve`](Self::resolve)
+    /// on columns of at least `index + 1` valid elements, borrowed for `'a`.
+    unsafe fn fetch(ptrs: Self::Ptrs, index: usize) -> Self;
+}
+
+/// A [`SoaQuery`] whose terms only read their columns, such as `(&Position, &Name)`.
+///
+/// # Safety
+///
+/// [`query_ref`](SliceSoa2::query_ref) runs these queries through a shared reference,
+/// so [`fetch`](SoaQuery::fetch) must never write through the column pointers.
+pub unsafe trait ReadOnlyQuery<'a>: SoaQuery<'a> {}
+
+/// An iterator over the rows of a query, yielding one tuple of references per row.
+pub struct QueryIter<'a, Q: SoaQuery<'a>> {
+    ptrs: Q::Ptrs,
+    index: usize,
+    len: usize,
+    lifetime: PhantomData<&'a mut ()>,
+}
+
+// SAFETY: The iterator only hands out the references in Q, one row at a time.
+unsafe impl<'a, Q: SoaQuery<'a> + Send> Send for QueryIter<'a, Q> {}
+unsafe impl<'a, Q: SoaQuery<'a> + Sync> Sync for QueryIter<'a, Q> {}
+
+impl<'a, Q: SoaQuery<'a>> Iterator for QueryIter<'a, Q> {
+    type Item = Q;
+
+    #[inline]
+    fn next(&mut self) -> Option<Q> {
+        if self.index >= self.len {
+            None
+        } else {
+            let item = unsafe {
+                // SAFETY: We check above that the index is within bounds, and each
+                // row is handed out at most once.
+                Q::fetch(self.ptrs, self.index)
+            };
+            self.index += 1;
+            Some(item)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, Q: SoaQuery<'a>> DoubleEndedIterator for QueryIter<'a, Q> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Q> {
+        if self.index >= self.len {
+            None
+        } else {
+            self.len -= 1;
+            unsafe {
+                // SAFETY: We check above that the new length is within bounds, and
+                // each row is handed out at most once.
+                Some(Q::fetch(self.ptrs, self.len))
+            }
+        }
+    }
+}
+
+impl<'a, Q: SoaQuery<'a>> ExactSizeIterator for QueryIter<'a, Q> {}
+impl<'a, Q: SoaQuery<'a>> FusedIterator for QueryIter<'a, Q> {}
+
+/// Finds the only column among `columns` that holds elements of type `T`.
+fn find_column<T: 'static>(columns: &[(TypeId, *mut u8)]) -> Result<*mut T, ColumnError> {
+    let type_id = TypeId::of::<T>();
+    let type_name = any::type_name::<T>();
+    let mut matches = columns.iter().filter(|(id, _)| *id == type_id);
+
+    match (matches.next(), matches.next()) {
+        (Some((_, ptr)), None) => Ok(ptr.cast::<T>()),
+        (Some(_), Some(_)) => Err(ColumnError::Ambiguous { type_name }),
+        (None, _) => Err(ColumnError::Missing { type_name }),
+    }
+}
+
+macro_rules! impl_query_n {
+    ($n:literal) => { seq!(J in 0..$n {
+        // SAFETY: Each term gets the only column paired with the type id of its element
+        // type, so terms share a column exactly when they share a type. Any term sharing
+        // a type with a mutable term is rejected before the lookup.
+        unsafe impl<'a, #(Q~J: QueryTerm<'a>,)*> SoaQuery<'a> for (#(Q~J,)*) {
+            type Ptrs = (#(*mut Q~J::Elem,)*);
+
+            #[inline]
+            fn resolve(columns: &[(TypeId, *mut u8)]) -> Result<Self::Ptrs, ColumnError> {
+                let terms = [#((TypeId::of::<Q~J::Elem>(), Q~J::MUTABLE, any::type_name::<Q~J::Elem>()),)*];
+
+                for (index, (type_id, mutable, type_name)) in terms.iter().enumerate() {
+                    let aliased = terms[..index]
+                        .iter()
+                        .any(|(other, other_mutable, _)| (other == type_id) && (*mutable || *other_mutable));
+
+                    if aliased {
+                        return Err(ColumnError::Aliased { type_name });
+                    }
+                }
+
+                Ok((#(find_column::<Q~J::Elem>(columns)?,)*))
+            }
+
+            #[inline(always)]
+            unsafe fn fetch(ptrs: Self::Ptrs, index: usize) -> Self {
+                // SAFETY: The caller upholds the guarantees for every term.
+                unsafe { (#(Q~J::fetch(ptrs.J, index),)*) }
+            }
+        }
+
+        // SAFETY: Every term is a ReadTerm, so fetching never writes to a column.
+        unsafe impl<'a, #(Q~J: ReadTerm<'a>,)*> ReadOnlyQuery<'a> for (#(Q~J,)*) {}
+    });}
+}
+
+seq!(K in 1..=12 {
+    impl_query_n!(K);
+});
+
+macro_rules! impl_n {
+    ($slice:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I: 'static,)*> $slice<#(T~I,)*> {
+            /// Returns the column holding elements of type `T`.
+            ///
+            /// Returns an error if no column or more than one column holds `T`.
+            #[inline]
+            pub fn column<T: 'static>(&self) -> Result<&[T], ColumnError> {
+                let columns = self.soa_slices();
+                let ptr = find_column::<T>(&[#((TypeId::of::<T~I>(), columns.I.as_ptr() as *mut u8),)*])?;
+
+                unsafe {
+                    // SAFETY: The column holds len valid elements of type T, and the
+                    // returned slice is only ever read through.
+                    Ok(slice::from_raw_parts(ptr, self.len))
+                }
+            }
+
+            /// Returns the column holding elements of type `T` as a mutable slice.
+            ///
+            /// Returns an error if no column or more than one column holds `T`.
+            #[inline]
+            pub fn column_mut<T: 'static>(&mut self) -> Result<&mut [T], ColumnError> {
+                let len = self.len;
+                let ptr = find_column::<T>(&self.column_ptrs_mut())?;
+
+                unsafe {
+                    // SAFETY: The column holds len valid elements of type T, and we hold
+                    // &mut self for the lifetime of the returned slice.
+                    Ok(slice::from_raw_parts_mut(ptr, len))
+                }
+            }
+
+            /// Returns an iterator over the rows of the columns named by `Q`, a tuple of
+            /// `&T` and `&mut T` terms such as `(&Position, &mut Velocity)`.
+            ///
+            /// Returns an error if any column is missing or ambiguous, or if a column is
+            /// borrowed mutably by one term while borrowed by another.
+            #[inline]
+            pub fn query<'a, Q: SoaQuery<'a>>(&'a mut self) -> Result<QueryIter<'a, Q>, ColumnError> {
+                let len = self.len;
+                let ptrs = Q::resolve(&self.column_ptrs_mut())?;
+
+                Ok(QueryIter {
+                    ptrs,
+                    index: 0,
+                    len,
+                    lifetime: PhantomData,
+                })
+            }
+
+            /// Returns an iterator over the rows of the columns named by `Q`, a tuple of
+            /// `&T` terms such as `(&Position, &Name)`.
+            ///
+            /// Unlike [`query`](Self::query), this only needs a shared reference, so any
+            /// number of these queries can be alive at once.
+            ///
+            /// Returns an error if any column is missing or ambiguous.
+            #[inline]
+            pub fn query_ref<'a, Q: ReadOnlyQuery<'a>>(&'a self) -> Result<QueryIter<'a, Q>, ColumnError> {
+                let columns = self.soa_slices();
+                let ptrs = Q::resolve(&[#((TypeId::of::<T~I>(), columns.I.as_ptr() as *mut u8),)*])?;
+
+                Ok(QueryIter {
+                    ptrs,
+                    index: 0,
+                    len: self.len,
+                    lifetime: PhantomData,
+                })
+            }
+
+            /// Returns the element type and a mutable pointer for every column.
+            #[inline(always)]
+            fn column_ptrs_mut(&mut self) -> [(TypeId, *mut u8); $n] {
+                let columns = self.soa_mut_slices();
+                [#((TypeId::of::<T~I>(), columns.I.as_mut_ptr() as *mut u8),)*]
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(SliceSoa~N, N);
+});
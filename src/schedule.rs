@@ -0,0 +1,290 @@
+//! A scheduler that runs closures over disjoint columns in parallel.
+//!
+//! Each system registered with a [`Schedule`] declares the columns it reads and
+//! writes as a tuple such as `(Read<0>, Read<1>, Write<2>)`, and receives exactly
+//! those column slices when it runs. Systems are grouped into batches where no
+//! column written by one system is accessed by another, and each batch runs on
+//! [`std::thread::scope`] workers. Systems that conflict always run in the order
+//! they were added.
+//!
+//! This module requires the `std` feature.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use std::thread;
+
+use seq_macro::seq;
+
+use crate::shared::{Read, Write};
+use crate::slice::SoaColumn;
+
+/// A single column access declared by a system: [`Read<K>`] or [`Write<K>`].
+///
+/// # Safety
+///
+/// A [`Schedule`] runs systems in parallel based only on `INDEX` and `WRITE`, so
+/// the implementation must uphold the following:
+/// - [`slice`](Self::slice) only accesses the column at `INDEX`
+/// - [`slice`](Self::slice) only hands out write access if `WRITE` is `true`
+/// - Anything written through the slice can be sent to another thread
+pub unsafe trait ColumnAccess<S> {
+    /// The slice handed to the system: `&[T]` or `&mut [T]`.
+    type Slice<'s>
+    where
+        S: 's;
+
+    /// The position of the column.
+    const INDEX: usize;
+
+    /// Whether the column is written.
+    const WRITE: bool;
+
+    /// Borrows the column from `slice`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that nothing else writes to the column
+    /// (or accesses it at all, for [`Write<K>`]) while the returned slice lives.
+    unsafe fn slice(slice: &S) -> Self::Slice<'_>;
+}
+
+// SAFETY: The slice only reads column K, which SoaColumn guarantees is the live
+// column K and disjoint from every other column.
+unsafe impl<S: SoaColumn<K>, const K: usize> ColumnAccess<S> for Read<K> {
+    type Slice<'s>
+        = &'s [S::Elem]
+    where
+        S: 's;
+
+    const INDEX: usize = K;
+    const WRITE: bool = false;
+
+    #[inline(always)]
+    unsafe fn slice(slice: &S) -> Self::Slice<'_> {
+        unsafe {
+            // SAFETY: The caller guarantees that nothing writes to the column.
+            SoaColumn::<K>::column_ptr(slice).as_ref()
+        }
+    }
+}
+
+// SAFETY: The slice only accesses column K, whose elements are Send. SoaColumn
+// guarantees that it is disjoint from every other column and valid for writes.
+unsafe impl<S: SoaColumn<K>, const K: usize> ColumnAccess<S> for Write<K>
+where
+    S::Elem: Send,
+{
+    type Slice<'s>
+        = &'s mut [S::Elem]
+    where
+        S: 's;
+
+    const INDEX: usize = K;
+    const WRITE: bool = true;
+
+    #[inline(always)]
+    unsafe fn slice(slice: &S) -> Self::Slice<'_> {
+        unsafe {
+            // SAFETY: The caller guarantees exclusive access to the column.
+            SoaColumn::<K>::column_ptr(slice).as_mut()
+        }
+    }
+}
+
+/// The tuple of column accesses declared by a system, such as `(Read<0>, Write<2>)`.
+///
+/// # Safety
+///
+/// [`accesses`](Self::accesses) must list every column that
+/// [`slices`](Self::slices) accesses, marked as written whenever it hands out write
+/// access to it, as in [`ColumnAccess`].
+pub unsafe trait AccessSet<S> {
+    /// The tuple of slices handed to the system.
+    type Slices<'s>
+    where
+        S: 's;
+
+    /// Returns the position of every accessed column, and whether it is written.
+    fn accesses() -> Vec<(usize, bool)>;
+
+    /// Borrows every column from `slice`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee the requirements of
+    /// [`ColumnAccess::slice`] for every column, and that no column is written by
+    /// one access while accessed by another.
+    unsafe fn slices(slice: &S) -> Self::Slices<'_>;
+}
+
+macro_rules! impl_set_n {
+    ($n:literal) => { seq!(J in 0..$n {
+        // SAFETY: Every slice comes from its own ColumnAccess, which is listed with
+        // its own INDEX and WRITE.
+        unsafe impl<S, #(C~J: ColumnAccess<S>,)*> AccessSet<S> for (#(C~J,)*) {
+            type Slices<'s> = (#(C~J::Slice<'s>,)*) where S: 's;
+
+            #[inline]
+            fn accesses() -> Vec<(usize, bool)> {
+                alloc::vec![#((C~J::INDEX, C~J::WRITE),)*]
+            }
+
+            #[inline(always)]
+            unsafe fn slices(slice: &S) -> Self::Slices<'_> {
+                // SAFETY: The caller upholds the guarantees for every column.
+                unsafe { (#(C~J::slice(slice),)*) }
+            }
+        }
+    });}
+}
+
+seq!(M in 1..=12 {
+    impl_set_n!(M);
+});
+
+struct System<'a, S> {
+    accesses: Vec<(usize, bool)>,
+    batch: usize,
+    run: Box<dyn FnMut(&S) + Send + 'a>,
+}
+
+/// A set of systems over the columns of a slice of type `S`, grouped into batches
+/// that can run in parallel.
+pub struct Schedule<'a, S> {
+    systems: Vec<System<'a, S>>,
+    batches: usize,
+}
+
+impl<'a, S> Schedule<'a, S> {
+    /// Creates an empty schedule.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            systems: Vec::new(),
+            batches: 0,
+        }
+    }
+
+    /// Adds a system that accesses the columns declared by `L`, such as
+    /// `(Read<0>, Write<2>)`, returning its index.
+    ///
+    /// The system is placed in the first batch after every earlier system it
+    /// conflicts with, so conflicting systems run in the order they were added.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `L` writes a column that it also accesses
+    /// elsewhere in the same tuple.
+    pub fn add_system<L: AccessSet<S>>(
+        &mut self,
+        mut system: impl for<'s> FnMut(L::Slices<'s>) + Send + 'a,
+    ) -> usize {
+        let accesses = L::accesses();
+
+        for (index, &(column, write)) in accesses.iter().enumerate() {
+            let aliased = accesses[..index]
+                .iter()
+                .any(|&(other, other_write)| (other == column) && (write || other_write));
+            assert!(
+                !aliased,
+                "column {column} is written and also accessed by the same system"
+            );
+        }
+
+        let batch = self
+            .systems
+            .iter()
+            .filter(|other| conflicts(&accesses, &other.accesses))
+            .map(|other| other.batch + 1)
+            .max()
+            .unwrap_or(0);
+
+        self.batches = usize::max(self.batches, batch + 1);
+        self.systems.push(System {
+            accesses,
+            batch,
+            run: Box::new(move |slice| unsafe {
+                // SAFETY: The schedule only runs systems together when none of them
+                // writes a column that another accesses.
+                system(L::slices(slice))
+            }),
+        });
+
+        self.systems.len() - 1
+    }
+
+    /// Returns the number of systems.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    /// Returns `true` if there are no systems.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Returns the number of batches, which run one after another.
+    #[inline(always)]
+    pub fn batch_count(&self) -> usize {
+        self.batches
+    }
+
+    /// Returns the batch that the system at `index` runs in.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if `index` is out of bounds.
+    #[inline]
+    pub fn batch_of(&self, index: usize) -> usize {
+        self.systems[index].batch
+    }
+
+    /// Runs every system over `slice`, one batch at a time.
+    ///
+    /// Within a batch, every system but one runs on its own scoped thread, and the
+    /// first one runs on the calling thread. If a system panics, the panic is
+    /// propagated once the rest of its batch has finished.
+    pub fn run(&mut self, slice: &mut S)
+    where
+        S: Sync,
+    {
+        // Nothing else can access the slice while we hold it mutably.
+        let slice = &*slice;
+
+        for batch in 0..self.batches {
+            let mut systems = self
+                .systems
+                .iter_mut()
+                .filter(|system| system.batch == batch);
+            let Some(first) = systems.next() else {
+                continue;
+            };
+
+            thread::scope(|scope| {
+                for system in systems {
+                    scope.spawn(move || (system.run)(slice));
+                }
+
+                (first.run)(slice);
+            });
+        }
+    }
+}
+
+impl<S> Default for Schedule<'_, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether two systems can't run at the same time.
+fn conflicts(a: &[(usize, bool)], b: &[(usize, bool)]) -> bool {
+    a.iter().any(|&(column, write)| {
+        b.iter()
+            .any(|&(other, other_write)| (column == other) && (write || other_write))
+    })
+}
@@ -0,0 +1,115 @@
+//! Static schema introspection and column-wise visitors.
+//!
+//! [`SoaSchema`] describes the columns of a structure-of-arrays type, and
+//! [`ColumnVisitor`] lets generic tooling (serializers, debug UIs, profilers) walk
+//! every column of a slice with its element type, without a macro arm per arity.
+
+use core::alloc::Layout;
+use core::any::{self, TypeId};
+
+use allocator_api2::alloc::Allocator;
+use seq_macro::seq;
+
+use crate::slice::*;
+use crate::vec::*;
+
+/// A description of a single column's element type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// The position of the column within the row tuple.
+    pub index: usize,
+    /// The layout of a single element of the column.
+    pub layout: Layout,
+    /// The name of the element type, as given by [`core::any::type_name`].
+    pub type_name: &'static str,
+    /// The unique identifier of the element type.
+    pub type_id: TypeId,
+}
+
+impl ColumnInfo {
+    /// Describes a column at `index` holding elements of type `T`.
+    #[inline]
+    pub fn of<T: 'static>(index: usize) -> Self {
+        Self {
+            index,
+            layout: Layout::new::<T>(),
+            type_name: any::type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+        }
+    }
+}
+
+/// Static information about the columns of a structure-of-arrays type.
+pub trait SoaSchema {
+    /// The number of columns.
+    const COLUMNS: usize;
+
+    /// Returns a description of the column at `index`, or `None` if it is out of bounds.
+    fn column(index: usize) -> Option<ColumnInfo>;
+
+    /// Returns a description of every column, in order.
+    #[inline]
+    fn columns() -> impl Iterator<Item = ColumnInfo> {
+        (0..Self::COLUMNS).filter_map(Self::column)
+    }
+}
+
+/// A visitor called once for each column of a slice, with that column's data.
+///
+/// Element types are `'static`, so a visitor can identify them with [`TypeId`] or
+/// downcast individual elements through [`Any`](core::any::Any).
+pub trait ColumnVisitor {
+    /// Visits the column at `index`.
+    fn visit<T: 'static>(&mut self, index: usize, column: &[T]);
+
+    /// Visits the column at `index` mutably. By default, this calls [`visit`](Self::visit).
+    #[inline]
+    fn visit_mut<T: 'static>(&mut self, index: usize, column: &mut [T]) {
+        self.visit(index, column);
+    }
+}
+
+macro_rules! impl_n {
+    ($vec:ident, $slice:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I: 'static,)*> SoaSchema for $slice<#(T~I,)*> {
+            const COLUMNS: usize = $n;
+
+            #[inline]
+            fn column(index: usize) -> Option<ColumnInfo> {
+                match index {
+                    #(I => Some(ColumnInfo::of::<T~I>(I)),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl<#(T~I: 'static,)* A: Allocator> SoaSchema for $vec<#(T~I,)* A> {
+            const COLUMNS: usize = $n;
+
+            #[inline]
+            fn column(index: usize) -> Option<ColumnInfo> {
+                <$slice<#(T~I,)*> as SoaSchema>::column(index)
+            }
+        }
+
+        impl<#(T~I: 'static,)*> $slice<#(T~I,)*> {
+            /// Calls [`ColumnVisitor::visit`] with each column, in order.
+            #[inline]
+            pub fn for_each_column<V: ColumnVisitor>(&self, visitor: &mut V) {
+                let columns = self.soa_slices();
+                #(visitor.visit(I, columns.I);)*
+            }
+
+            /// Calls [`ColumnVisitor::visit_mut`] with each column, in order.
+            #[inline]
+            pub fn for_each_column_mut<V: ColumnVisitor>(&mut self, visitor: &mut V) {
+                let columns = self.soa_mut_slices();
+                #(visitor.visit_mut(I, columns.I);)*
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    impl_n!(VecSoa~N, SliceSoa~N, N);
+});
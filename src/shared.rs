@@ -0,0 +1,404 @@
+//! Thread-safe per-column locking for sharing one table across threads.
+//!
+//! A `SharedVecSoaN` guards each column with its own [`RwLock`], plus a structural
+//! lock for anything that changes the rows themselves (pushing, removing, growing).
+//! Column guards hold the structural lock for reading, so any number of threads can
+//! read and write different columns at once while no rows are added or removed.
+//!
+//! Locks are always taken in the same order: the structural lock first, then the
+//! column locks by ascending position. [`lock_columns`](SharedVecSoa2::lock_columns)
+//! takes several columns in that order at once, so two threads locking overlapping
+//! sets of columns can never deadlock. Taking a second guard on a thread that
+//! already holds one can still deadlock against a waiting writer, as with any
+//! [`RwLock`], so lock every column a task needs in a single call.
+//!
+//! This module requires the `std` feature.
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use allocator_api2::alloc::{Allocator, Global};
+use seq_macro::seq;
+
+use crate::slice::*;
+use crate::vec::*;
+
+/// Requests a shared lock on the column at position `K`, for [`ColumnSet`].
+pub struct Read<const K: usize>;
+
+/// Requests an exclusive lock on the column at position `K`, for [`ColumnSet`].
+pub struct Write<const K: usize>;
+
+/// A shared lock on a single column, which derefs to the column's data.
+pub struct ReadColumn<'a, T> {
+    column: NonNull<[T]>,
+    _lock: RwLockReadGuard<'a, ()>,
+}
+
+impl<T> Deref for ReadColumn<'_, T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        unsafe {
+            // SAFETY: The column's read lock guarantees that nothing writes to it.
+            self.column.as_ref()
+        }
+    }
+}
+
+/// An exclusive lock on a single column, which derefs to the column's data.
+pub struct WriteColumn<'a, T> {
+    column: NonNull<[T]>,
+    _lock: RwLockWriteGuard<'a, ()>,
+}
+
+impl<T> Deref for WriteColumn<'_, T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        unsafe {
+            // SAFETY: The column's write lock guarantees exclusive access.
+            self.column.as_ref()
+        }
+    }
+}
+
+impl<T> DerefMut for WriteColumn<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe {
+            // SAFETY: The column's write lock guarantees exclusive access.
+            self.column.as_mut()
+        }
+    }
+}
+
+/// Column locks together with the structural read lock that keeps the rows in place.
+///
+/// This derefs to the column guards: a single [`ReadColumn`] or [`WriteColumn`], or
+/// a tuple of them for [`lock_columns`](SharedVecSoa2::lock_columns).
+pub struct Columns<'a, G> {
+    // Declared first, so the column locks are released before the structural lock.
+    columns: G,
+    _structure: RwLockReadGuard<'a, ()>,
+}
+
+impl<G> Deref for Columns<'_, G> {
+    type Target = G;
+
+    #[inline(always)]
+    fn deref(&self) -> &G {
+        &self.columns
+    }
+}
+
+impl<G> DerefMut for Columns<'_, G> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.columns
+    }
+}
+
+/// A request to lock a single column: [`Read<K>`] or [`Write<K>`].
+///
+/// # Safety
+///
+/// Other threads may access every other column of `slice` while the guard lives, so
+/// the implementation must uphold the following:
+/// - The guard only accesses the column at `INDEX`, and holds `lock` while it lives
+/// - The guard only writes to the column if it holds `lock` exclusively
+pub unsafe trait ColumnLock<'a, S> {
+    /// The guard for the locked column.
+    type Guard;
+
+    /// The position of the column.
+    const INDEX: usize;
+
+    /// Acquires `lock` and borrows the column from `slice`.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `lock` is the lock for this column
+    /// of `slice`, and that the rows of `slice` can't change while the guard lives.
+    unsafe fn lock(lock: &'a RwLock<()>, slice: &'a S) -> Self::Guard;
+}
+
+// SAFETY: The guard holds the shared lock and only reads column K, which SoaColumn
+// guarantees is disjoint from every other column.
+unsafe impl<'a, S: SoaColumn<K>, const K: usize> ColumnLock<'a, S> for Read<K>
+where
+    S::Elem: 'a,
+{
+    type Guard = ReadColumn<'a, S::Elem>;
+    const INDEX: usize = K;
+
+    #[inline]
+    unsafe fn lock(lock: &'a RwLock<()>, slice: &'a S) -> Self::Guard {
+        // A panic while holding a column lock can't leave the table inconsistent.
+        let lock = lock.read().unwrap_or_else(PoisonError::into_inner);
+
+        ReadColumn {
+            column: SoaColumn::<K>::column_ptr(slice),
+            _lock: lock,
+        }
+    }
+}
+
+// SAFETY: The guard holds the exclusive lock and only accesses column K, which
+// SoaColumn guarantees is disjoint from every other column and valid for writes.
+unsafe impl<'a, S: SoaColumn<K>, const K: usize> ColumnLock<'a, S> for Write<K>
+where
+    S::Elem: 'a,
+{
+    type Guard = WriteColumn<'a, S::Elem>;
+    const INDEX: usize = K;
+
+    #[inline]
+    unsafe fn lock(lock: &'a RwLock<()>, slice: &'a S) -> Self::Guard {
+        // A panic while holding a column lock can't leave the table inconsistent.
+        let lock = lock.write().unwrap_or_else(PoisonError::into_inner);
+
+        WriteColumn {
+            column: SoaColumn::<K>::column_ptr(slice),
+            _lock: lock,
+        }
+    }
+}
+
+/// A tuple of column lock requests, such as `(Read<0>, Write<2>)`.
+///
+/// # Safety
+///
+/// The guards must only access columns of `slice` whose locks they hold, as in
+/// [`ColumnLock`], and locks must be acquired in ascending column order so that
+/// threads locking overlapping sets of columns can't deadlock.
+pub unsafe trait ColumnSet<'a, S> {
+    /// The tuple of guards for the locked columns.
+    type Guards;
+
+    /// Acquires every column lock in ascending column order.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the caller to guarantee that `locks` holds the lock for every
+    /// column of `slice`, and that the rows of `slice` can't change while the guards
+    /// live.
+    ///
+    /// # Panics
+    ///
+    /// This operation will panic if the same column is requested more than once.
+    unsafe fn lock(locks: &'a [RwLock<()>], slice: &'a S) -> Self::Guards;
+}
+
+macro_rules! impl_set_n {
+    ($n:literal) => { seq!(J in 0..$n {
+        // SAFETY: Every guard comes from its own ColumnLock, which holds the lock for
+        // its column, and the locks are acquired in ascending order of their index.
+        unsafe impl<'a, S, #(L~J: ColumnLock<'a, S>,)*> ColumnSet<'a, S> for (#(L~J,)*) {
+            type Guards = (#(L~J::Guard,)*);
+
+            #[inline]
+            unsafe fn lock(locks: &'a [RwLock<()>], slice: &'a S) -> Self::Guards {
+                let mut order = [#((L~J::INDEX, J),)*];
+                order.sort_unstable();
+
+                for pair in order.windows(2) {
+                    assert!(pair[0].0 != pair[1].0, "column {} is locked more than once", pair[0].0);
+                }
+
+                let mut guards = (#(None::<L~J::Guard>,)*);
+                for (index, term) in order {
+                    match term {
+                        // SAFETY: The caller upholds the guarantees for every column.
+                        #(J => guards.J = Some(unsafe { L~J::lock(&locks[index], slice) }),)*
+                        _ => unreachable!(),
+                    }
+                }
+
+                (#(guards.J.unwrap(),)*)
+            }
+        }
+    });}
+}
+
+seq!(M in 1..=12 {
+    impl_set_n!(M);
+});
+
+macro_rules! impl_n {
+    ($shared:ident, $vec:ident, $slice:ident, $n:literal) => { seq!(I in 0..$n {
+        /// A vector that can be shared between threads, with a lock per column and
+        /// a structural lock for changing its rows.
+        pub struct $shared<#(T~I,)* A: Allocator = Global> {
+            vec: UnsafeCell<$vec<#(T~I,)* A>>,
+            structure: RwLock<()>,
+            columns: [RwLock<()>; $n],
+        }
+
+        // SAFETY: Columns are only written under their exclusive lock, and the vector
+        // is only changed under the exclusive structural lock, which makes sharing
+        // the vector equivalent to sending its contents between threads.
+        unsafe impl<#(T~I: Send + Sync,)* A: Allocator + Send> Sync for $shared<#(T~I,)* A> {}
+
+        impl<#(T~I,)* A: Allocator> $shared<#(T~I,)* A> {
+            /// Wraps the given vector.
+            #[inline]
+            pub fn new(vec: $vec<#(T~I,)* A>) -> Self {
+                Self {
+                    vec: UnsafeCell::new(vec),
+                    structure: RwLock::new(()),
+                    columns: Default::default(),
+                }
+            }
+
+            /// Unwraps the vector.
+            #[inline]
+            pub fn into_inner(self) -> $vec<#(T~I,)* A> {
+                self.vec.into_inner()
+            }
+
+            /// Returns the vector for unlocked access.
+            ///
+            /// This requires a mutable reference, so no lock can be held.
+            #[inline(always)]
+            pub fn get_mut(&mut self) -> &mut $vec<#(T~I,)* A> {
+                self.vec.get_mut()
+            }
+
+            /// Returns the number of rows, briefly taking the structural lock for reading.
+            #[inline]
+            pub fn len(&self) -> usize {
+                let _structure = self.structure.read().unwrap_or_else(PoisonError::into_inner);
+
+                unsafe {
+                    // SAFETY: The structural lock guarantees that nothing changes the rows.
+                    (*self.vec.get()).len()
+                }
+            }
+
+            /// Returns `true` if there are no rows, briefly taking the structural lock
+            /// for reading.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Takes the structural lock exclusively, blocking until every column
+            /// guard is released, and returns the vector for pushing or removing rows.
+            #[inline]
+            pub fn lock_structure(&self) -> StructureGuard<'_, $vec<#(T~I,)* A>> {
+                // The vector's own operations leave it consistent if they panic.
+                let lock = self.structure.write().unwrap_or_else(PoisonError::into_inner);
+
+                StructureGuard {
+                    vec: unsafe {
+                        // SAFETY: Every other access holds the structural lock.
+                        &mut *self.vec.get()
+                    },
+                    _lock: lock,
+                }
+            }
+
+            /// Pushes a new row onto the end of the vector under the structural lock,
+            /// returning its index.
+            #[inline]
+            pub fn push(&self, row: (#(T~I,)*)) -> usize {
+                let mut vec = self.lock_structure();
+                vec.push(row);
+                vec.len() - 1
+            }
+
+            /// Locks the column at position `K` for reading.
+            #[inline]
+            pub fn read_column<const K: usize>(
+                &self,
+            ) -> Columns<'_, ReadColumn<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                self.lock_columns::<(Read<K>,)>().map_single()
+            }
+
+            /// Locks the column at position `K` for writing.
+            #[inline]
+            pub fn write_column<const K: usize>(
+                &self,
+            ) -> Columns<'_, WriteColumn<'_, <$slice<#(T~I,)*> as SoaColumn<K>>::Elem>>
+            where
+                $slice<#(T~I,)*>: SoaColumn<K>,
+            {
+                self.lock_columns::<(Write<K>,)>().map_single()
+            }
+
+            /// Locks several columns at once, in ascending column order, given as a
+            /// tuple of requests such as `(Read<0>, Write<2>)`.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if the same column is requested more than once.
+            #[inline]
+            pub fn lock_columns<'a, L: ColumnSet<'a, $slice<#(T~I,)*>>>(&'a self) -> Columns<'a, L::Guards> {
+                let structure = self.structure.read().unwrap_or_else(PoisonError::into_inner);
+
+                let columns = unsafe {
+                    // SAFETY: The structural lock guarantees that nothing changes the
+                    // rows until the guards are dropped, and we pass our own locks.
+                    L::lock(&self.columns, &(*self.vec.get()).slice)
+                };
+
+                Columns {
+                    columns,
+                    _structure: structure,
+                }
+            }
+        }
+
+        impl<#(T~I,)* A: Allocator> From<$vec<#(T~I,)* A>> for $shared<#(T~I,)* A> {
+            #[inline]
+            fn from(vec: $vec<#(T~I,)* A>) -> Self {
+                Self::new(vec)
+            }
+        }
+    });}
+}
+
+impl<'a, G> Columns<'a, (G,)> {
+    #[inline(always)]
+    fn map_single(self) -> Columns<'a, G> {
+        Columns {
+            columns: self.columns.0,
+            _structure: self._structure,
+        }
+    }
+}
+
+/// Exclusive access to a shared vector's rows, holding its structural lock.
+pub struct StructureGuard<'a, V> {
+    vec: &'a mut V,
+    _lock: RwLockWriteGuard<'a, ()>,
+}
+
+impl<V> Deref for StructureGuard<'_, V> {
+    type Target = V;
+
+    #[inline(always)]
+    fn deref(&self) -> &V {
+        self.vec
+    }
+}
+
+impl<V> DerefMut for StructureGuard<'_, V> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut V {
+        self.vec
+    }
+}
+
+seq!(N in 2..=12 {
+    impl_n!(SharedVecSoa~N, VecSoa~N, SliceSoa~N, N);
+});
@@ -1,12 +1,46 @@
-use std::marker::PhantomData;
-use std::ops::{Deref, DerefMut};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
 
+#[cfg(feature = "alloc")]
+use allocator_api2::alloc::Allocator;
 use seq_macro::seq;
 
 use crate::data::RawDataPtr;
 use crate::index::IndexSoa;
+#[cfg(feature = "alloc")]
 use crate::vec::*;
 
+/// Access to the column at position `K` of a structure-of-arrays slice, for code
+/// that is generic over which column it touches.
+///
+/// # Safety
+///
+/// Column locks and schedules build references from [`column_ptr`](Self::column_ptr)
+/// for any type that implements this trait, so the implementation must uphold the
+/// following:
+/// - `column_ptr` points to the live, initialized column `K`, with one element per row
+/// - It is valid for writes as long as the slice is, even behind a shared reference
+/// - It never overlaps with the column returned for any other `K`
+/// - `column_at` and `column_at_mut` return that same column
+pub unsafe trait SoaColumn<const K: usize> {
+    /// The element type of the column.
+    type Elem;
+
+    /// Returns the column at position `K`.
+    fn column_at(&self) -> &[Self::Elem];
+
+    /// Returns the column at position `K` as a mutable slice.
+    fn column_at_mut(&mut self) -> &mut [Self::Elem];
+
+    /// Returns a raw pointer to the column at position `K`.
+    ///
+    /// The pointer is not derived from a reference to the column, so it can be
+    /// written through while other columns are borrowed, as long as nothing else
+    /// accesses this column.
+    fn column_ptr(&self) -> NonNull<[Self::Elem]>;
+}
+
 macro_rules! impl_n {
     (
         $vec:ident,
@@ -89,6 +123,17 @@ macro_rules! impl_n {
                 }
             }
 
+            /// Returns every column as a slice of [`Cell`](core::cell::Cell)s, so that
+            /// rows can be updated through shared references.
+            #[inline]
+            pub fn as_cell_columns(&mut self) -> (#(&[core::cell::Cell<T~I>],)*) {
+                let slices = self.soa_mut_slices();
+                (#(core::cell::Cell::from_mut(slices.I).as_slice_of_cells(),)*)
+            }
+
+            /// # Safety
+            ///
+            /// It is up to the caller to guarantee that the index is within bounds.
             #[inline]
             pub unsafe fn get_unchecked<'a, I: IndexSoa<Self>>(
                 &'a self,
@@ -100,6 +145,9 @@ macro_rules! impl_n {
                 }
             }
 
+            /// # Safety
+            ///
+            /// It is up to the caller to guarantee that the index is within bounds.
             #[inline]
             pub unsafe fn get_unchecked_mut<'a, I: IndexSoa<Self>>(
                 &'a mut self,
@@ -112,8 +160,9 @@ macro_rules! impl_n {
             }
 
             /// Shallow-copies the slice, returning a slice that points to the same data.
+            #[cfg(feature = "alloc")]
             #[inline(always)]
-            fn shallow_copy(&self) -> Self {
+            pub(crate) fn shallow_copy(&self) -> Self {
                 $slice {
                     len: self.len,
                     data: (#(self.data.I.shallow_copy(),)*),
@@ -121,9 +170,10 @@ macro_rules! impl_n {
             }
         }
 
-        impl<#(T~I: Sized,)*> $vec<#(T~I,)*> {
+        #[cfg(feature = "alloc")]
+        impl<#(T~I: Sized,)* A: Allocator> $vec<#(T~I,)* A> {
             #[inline]
-            pub fn as_slice(&self) -> $slice_ref<#(T~I,)*> {
+            pub fn as_slice(&self) -> $slice_ref<'_, #(T~I,)*> {
                 $slice_ref {
                     slice: self.slice.shallow_copy(),
                     lifetime: PhantomData,
@@ -131,7 +181,7 @@ macro_rules! impl_n {
             }
 
             #[inline]
-            pub fn as_mut_slice(&mut self) -> $slice_ref_mut<#(T~I,)*> {
+            pub fn as_mut_slice(&mut self) -> $slice_ref_mut<'_, #(T~I,)*> {
                 $slice_ref_mut {
                     slice: self.slice.shallow_copy(),
                     lifetime: PhantomData,
@@ -164,7 +214,8 @@ macro_rules! impl_n {
             }
         }
 
-        impl<'a, #(T~I: Sized,)*> Deref for $vec<#(T~I,)*> {
+        #[cfg(feature = "alloc")]
+        impl<#(T~I: Sized,)* A: Allocator> Deref for $vec<#(T~I,)* A> {
             type Target = $slice<#(T~I,)*>;
 
             #[inline]
@@ -173,7 +224,8 @@ macro_rules! impl_n {
             }
         }
 
-        impl<'a, #(T~I: Sized,)*> DerefMut for $vec<#(T~I,)*> {
+        #[cfg(feature = "alloc")]
+        impl<#(T~I: Sized,)* A: Allocator> DerefMut for $vec<#(T~I,)* A> {
             #[inline]
             fn deref_mut(&mut self) -> &mut Self::Target {
                 &mut self.slice
@@ -182,6 +234,45 @@ macro_rules! impl_n {
     });}
 }
 
+macro_rules! impl_column_n {
+    ($slice:ident, $n:literal) => { seq!(I in 0..$n {
+        impl_column_k!($slice, $n, (#(T~I,)*));
+    });}
+}
+
+macro_rules! impl_column_k {
+    ($slice:ident, $n:literal, ($($t:ident,)*)) => { seq!(K in 0..$n {
+        // SAFETY: Every column has its own allocation of at least len initialized
+        // elements, and RawDataPtr doesn't derive its pointer from a reference.
+        unsafe impl<$($t: Sized,)*> SoaColumn<K> for $slice<$($t,)*> {
+            type Elem = T~K;
+
+            #[inline(always)]
+            fn column_at(&self) -> &[T~K] {
+                unsafe {
+                    // SAFETY: We guarantee that the data is valid in the range 0..self.len.
+                    self.data.K.slice(0, self.len)
+                }
+            }
+
+            #[inline(always)]
+            fn column_at_mut(&mut self) -> &mut [T~K] {
+                unsafe {
+                    // SAFETY: We guarantee that the data is valid in the range 0..self.len.
+                    self.data.K.slice_mut(0, self.len)
+                }
+            }
+
+            #[inline(always)]
+            fn column_ptr(&self) -> NonNull<[T~K]> {
+                let ptr = self.data.K.shallow_copy().raw_ptr().cast::<T~K>();
+                NonNull::slice_from_raw_parts(ptr, self.len)
+            }
+        }
+    });}
+}
+
 seq!(N in 2..=12 {
     impl_n!(VecSoa~N, SliceSoa~N, SliceSoaRef~N, SliceSoaRefMut~N, N);
+    impl_column_n!(SliceSoa~N, N);
 });
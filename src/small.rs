@@ -0,0 +1,625 @@
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ptr::{self, NonNull};
+
+use allocator_api2::alloc::{Allocator, Global};
+use seq_macro::seq;
+
+use crate::data::{handle_reserve_error, AllocGuard, DropGuard, RawDataPtr, ResizeGuard};
+use crate::error::TryReserveError;
+use crate::growth::GrowthPolicy;
+use crate::slice::*;
+#[cfg(feature = "stats")]
+use crate::stats::AllocStats;
+use crate::stats::StatsCounter;
+
+macro_rules! declare_small_n {
+    (
+        $small:ident,
+        $storage:ident,
+        $slice:ident,
+        $slice_ref:ident,
+        $slice_ref_mut:ident,
+        $n:literal
+    ) => { seq!(I in 0..$n {
+        /// Where the columns of a small vector currently live.
+        // Clippy sizes the inline variant for INLINE = 0, and boxing either one would
+        // defeat the point of storing the rows inline.
+        #[allow(clippy::large_enum_variant)]
+        enum $storage<const INLINE: usize, #(T~I: Sized,)*> {
+            /// Every column is stored inline, with room for `INLINE` rows.
+            Inline(UnsafeCell<(#([MaybeUninit<T~I>; INLINE],)*)>),
+            /// Every column is allocated on the heap, with room for `capacity` rows. The
+            /// capacity is always greater than `INLINE`.
+            Heap {
+                capacity: usize,
+                data: (#(RawDataPtr<T~I>,)*),
+            },
+        }
+
+        impl<const INLINE: usize, #(T~I: Sized,)*> $storage<INLINE, #(T~I,)*> {
+            #[inline(always)]
+            fn new_inline() -> Self {
+                Self::Inline(UnsafeCell::new((#([const { MaybeUninit::uninit() }; INLINE],)*)))
+            }
+        }
+
+        /// A vector that stores up to `INLINE` rows inline before spilling to the heap.
+        ///
+        /// While it fits, every column lives in an inline `[MaybeUninit<T>; INLINE]`
+        /// array, so small tables never allocate. Once it outgrows that, the rows move
+        /// into heap columns with the growth policy, capacity lock and allocation
+        /// statistics of a regular vector. The columns can't have a custom column
+        /// layout, since the inline ones are only aligned for their own type.
+        ///
+        /// The inline columns move along with the vector, so the slice API is available
+        /// through the views returned by [`as_slice`](Self::as_slice) and
+        /// [`as_mut_slice`](Self::as_mut_slice), which borrow the vector in place.
+        pub struct $small<const INLINE: usize, #(T~I: Sized,)* A: Allocator = Global> {
+            len: usize,
+            storage: $storage<INLINE, #(T~I,)*>,
+            growth: GrowthPolicy,
+            capacity_locked: bool,
+            stats: StatsCounter,
+            alloc: A,
+        }
+
+        // SAFETY: The inline columns are only written through a mutable borrow of the
+        // vector, so sharing the vector only ever shares its elements.
+        unsafe impl<const INLINE: usize, #(T~I: Sync,)* A: Allocator + Sync> Sync
+            for $small<INLINE, #(T~I,)* A>
+        {
+        }
+
+        impl<const INLINE: usize, #(T~I: Sized,)*> $small<INLINE, #(T~I,)*> {
+            #[inline]
+            pub fn new() -> Self {
+                Self::new_in(Global)
+            }
+
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self::with_capacity_in(capacity, Global)
+            }
+
+            /// Creates a new vector with room for the given number of rows.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+                Self::try_with_capacity_in(capacity, Global)
+            }
+        }
+
+        impl<const INLINE: usize, #(T~I: Sized,)* A: Allocator> $small<INLINE, #(T~I,)* A> {
+            /// Creates a new, empty vector that spills its columns into `alloc`.
+            #[inline]
+            pub fn new_in(alloc: A) -> Self {
+                Self {
+                    len: 0,
+                    storage: $storage::new_inline(),
+                    growth: GrowthPolicy::default(),
+                    capacity_locked: false,
+                    stats: StatsCounter::default(),
+                    alloc,
+                }
+            }
+
+            /// Creates a new vector with room for the given number of rows, spilling its
+            /// columns into `alloc` if that exceeds the inline capacity.
+            #[inline]
+            pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+                match Self::try_with_capacity_in(capacity, alloc) {
+                    Ok(vec) => vec,
+                    Err(err) => handle_reserve_error(err),
+                }
+            }
+
+            /// Creates a new vector with room for the given number of rows, spilling its
+            /// columns into `alloc` if that exceeds the inline capacity.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+                let mut vec = Self::new_in(alloc);
+                vec.try_reserve_exact(capacity)?;
+                Ok(vec)
+            }
+
+            /// Returns a reference to the allocator used once the columns spill.
+            #[inline(always)]
+            pub fn allocator(&self) -> &A {
+                &self.alloc
+            }
+
+            #[inline(always)]
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            #[inline(always)]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            #[inline(always)]
+            pub fn capacity(&self) -> usize {
+                match self.storage {
+                    $storage::Inline(_) => INLINE,
+                    $storage::Heap { capacity, .. } => capacity,
+                }
+            }
+
+            /// Returns the number of rows that fit without allocating.
+            #[inline(always)]
+            pub fn inline_capacity(&self) -> usize {
+                INLINE
+            }
+
+            /// Returns whether the columns have spilled onto the heap.
+            #[inline(always)]
+            pub fn spilled(&self) -> bool {
+                matches!(self.storage, $storage::Heap { .. })
+            }
+
+            /// Locks the capacity, so that any operation that would spill, reallocate or
+            /// move the columns back inline panics instead.
+            ///
+            /// The lock is only enforced with debug assertions enabled. In release builds,
+            /// this has no effect on any other operation.
+            #[inline(always)]
+            pub fn lock_capacity(&mut self) {
+                self.capacity_locked = true;
+            }
+
+            /// Unlocks the capacity after a call to [`lock_capacity`](Self::lock_capacity).
+            #[inline(always)]
+            pub fn unlock_capacity(&mut self) {
+                self.capacity_locked = false;
+            }
+
+            /// Returns whether the capacity is locked with [`lock_capacity`](Self::lock_capacity).
+            #[inline(always)]
+            pub fn is_capacity_locked(&self) -> bool {
+                self.capacity_locked
+            }
+
+            /// Returns the allocation counters for this vector's heap columns.
+            #[cfg(feature = "stats")]
+            #[inline]
+            pub fn alloc_stats(&self) -> AllocStats {
+                self.stats.get()
+            }
+
+            /// Resets the allocation counters for this vector's heap columns to zero.
+            #[cfg(feature = "stats")]
+            #[inline]
+            pub fn reset_alloc_stats(&mut self) {
+                self.stats.reset();
+            }
+
+            /// Returns the policy used to compute new capacities when growing.
+            #[inline(always)]
+            pub fn growth_policy(&self) -> GrowthPolicy {
+                self.growth
+            }
+
+            /// Sets the policy used to compute new capacities when growing.
+            #[inline(always)]
+            pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+                self.growth = growth;
+            }
+
+            /// Returns this vector with the given growth policy.
+            #[inline(always)]
+            pub fn with_growth_policy(mut self, growth: GrowthPolicy) -> Self {
+                self.growth = growth;
+                self
+            }
+
+            /// Checks that all of this vector's invariants hold.
+            ///
+            /// With the `checked` feature enabled, this also checks that every heap column
+            /// is allocated to exactly `self.capacity()` elements and that exactly the
+            /// first `self.len()` elements of every heap column are initialized.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic with a description of the first violated invariant.
+            #[inline]
+            pub fn validate(&self) {
+                assert!(
+                    self.len <= self.capacity(),
+                    "{}::validate: len {} exceeds capacity {}",
+                    stringify!($small),
+                    self.len,
+                    self.capacity(),
+                );
+
+                if let $storage::Heap { capacity, data } = &self.storage {
+                    assert!(
+                        *capacity > INLINE,
+                        "{}::validate: heap capacity {} fits in inline capacity {}",
+                        stringify!($small),
+                        capacity,
+                        INLINE,
+                    );
+
+                    #(data.I.validate(*capacity, self.len);)*
+                }
+            }
+
+            #[inline]
+            pub fn push(&mut self, data: (#(T~I,)*)) {
+                if let Err(err) = self.try_push(data) {
+                    handle_reserve_error(err);
+                }
+            }
+
+            /// Appends a row to the back of the vector, returning an error if the
+            /// vector needed to spill or grow and the allocation failed.
+            ///
+            /// On error, the vector is left unchanged and `data` is dropped.
+            #[inline]
+            pub fn try_push(&mut self, data: (#(T~I,)*)) -> Result<(), TryReserveError> {
+                self.try_reserve(1)?;
+                let mut slice = self.raw_slice();
+
+                unsafe {
+                    // SAFETY: try_reserve left room for the row, and we guarantee that
+                    // the data at self.len is invalid.
+                    #(slice.data.I.write(self.len, data.I);)*
+                }
+
+                self.len += 1;
+                Ok(())
+            }
+
+            #[inline]
+            pub fn reserve(&mut self, len: usize) {
+                if let Err(err) = self.try_reserve(len) {
+                    handle_reserve_error(err);
+                }
+            }
+
+            /// Reserves capacity for exactly `len` more rows, without over-allocating
+            /// according to the growth policy.
+            #[inline]
+            pub fn reserve_exact(&mut self, len: usize) {
+                if let Err(err) = self.try_reserve_exact(len) {
+                    handle_reserve_error(err);
+                }
+            }
+
+            /// Tries to reserve capacity for at least `len` more rows.
+            ///
+            /// On error, the vector is left with its original storage and contents.
+            #[inline]
+            pub fn try_reserve(&mut self, len: usize) -> Result<(), TryReserveError> {
+                let required = self
+                    .len
+                    .checked_add(len)
+                    .ok_or(TryReserveError::CapacityOverflow)?;
+                let capacity = self.capacity();
+
+                if required > capacity {
+                    self.check_unlocked()?;
+                    // While inline, this grows as if the inline columns were the previous
+                    // allocation.
+                    let elem_size = [#(mem::size_of::<T~I>(),)*].into_iter().max().unwrap_or(0);
+                    let new_capacity = self.growth.next_capacity(capacity, required, elem_size);
+                    self.try_resize(new_capacity)?;
+                }
+
+                Ok(())
+            }
+
+            /// Tries to reserve capacity for exactly `len` more rows.
+            ///
+            /// On error, the vector is left with its original storage and contents.
+            #[inline]
+            pub fn try_reserve_exact(&mut self, len: usize) -> Result<(), TryReserveError> {
+                let required = self
+                    .len
+                    .checked_add(len)
+                    .ok_or(TryReserveError::CapacityOverflow)?;
+
+                if required > self.capacity() {
+                    self.check_unlocked()?;
+                    self.try_resize(required)?;
+                }
+
+                Ok(())
+            }
+
+            /// Shrinks the capacity of every column as much as possible, moving the
+            /// columns back inline if the rows fit.
+            #[inline]
+            pub fn shrink_to_fit(&mut self) {
+                self.shrink_to(0);
+            }
+
+            /// Shrinks the capacity of every column with a lower bound.
+            ///
+            /// The capacity will remain at least as large as the length, `min_capacity`,
+            /// and the inline capacity. If it fits, the columns are moved back inline.
+            #[inline]
+            pub fn shrink_to(&mut self, min_capacity: usize) {
+                let $storage::Heap { capacity, .. } = self.storage else {
+                    return;
+                };
+
+                let new_capacity = usize::max(self.len, min_capacity);
+
+                if new_capacity >= capacity {
+                    return;
+                }
+
+                self.assert_unlocked("shrink_to");
+
+                if new_capacity <= INLINE {
+                    self.unspill();
+                } else if let Err(err) = self.try_resize(new_capacity) {
+                    handle_reserve_error(err);
+                }
+            }
+
+            #[inline]
+            pub fn clear(&mut self) {
+                let slice = self.raw_slice();
+
+                // Set the length first, so that a panicking destructor can never cause
+                // any element to be observed or dropped again after this call.
+                let len = mem::replace(&mut self.len, 0);
+
+                let _guards = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len,
+                    // and nothing else can access that data now that self.len is 0.
+                    (#(DropGuard::new(&slice.data.I, len),)*)
+                };
+            }
+
+            #[inline]
+            pub fn swap_remove(&mut self, index: usize) -> (#(T~I,)*) {
+                assert!(index < self.len);
+
+                let mut slice = self.raw_slice();
+                let len = self.len;
+                self.len -= 1;
+
+                unsafe {
+                    // SAFETY: We guarantee that the data in the range 0..len is valid,
+                    // and we check that the index is within bounds using the assert above.
+                    (#(slice.data.I.swap_remove(index, len),)*)
+                }
+            }
+
+            #[inline]
+            pub fn as_slice(&self) -> $slice_ref<'_, #(T~I,)*> {
+                $slice_ref {
+                    slice: self.raw_slice(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> $slice_ref_mut<'_, #(T~I,)*> {
+                $slice_ref_mut {
+                    slice: self.raw_slice(),
+                    lifetime: PhantomData,
+                }
+            }
+
+            /// Returns a slice over the rows, pointing into wherever the columns are
+            /// stored right now.
+            #[inline(always)]
+            fn raw_slice(&self) -> $slice<#(T~I,)*> {
+                let data = match &self.storage {
+                    $storage::Inline(columns) => {
+                        let columns = columns.get();
+
+                        let data = unsafe {
+                            // SAFETY: Taking field addresses doesn't create any references,
+                            // and each inline column holds INLINE elements owned by self.
+                            (#(
+                                RawDataPtr::from_block(
+                                    NonNull::new_unchecked(ptr::addr_of_mut!((*columns).I)).cast(),
+                                    INLINE,
+                                ),
+                            )*)
+                        };
+
+                        #(data.I.mark_init(0, self.len);)*
+                        data
+                    }
+                    $storage::Heap { data, .. } => (#(data.I.shallow_copy(),)*),
+                };
+
+                $slice { len: self.len, data }
+            }
+
+            /// Resizes every column to `new_capacity` rows, which must not fit inline.
+            ///
+            /// Inline columns are spilled into new heap columns, and heap columns are
+            /// resized in place where the allocator can. Either way, an error leaves the
+            /// vector exactly as it was. Callers check the capacity lock themselves.
+            fn try_resize(&mut self, new_capacity: usize) -> Result<(), TryReserveError> {
+                debug_assert!(new_capacity > INLINE);
+                debug_assert!(new_capacity >= self.len);
+
+                let $storage::Heap { capacity, data } = &mut self.storage else {
+                    return self.try_spill(new_capacity);
+                };
+
+                let old_capacity = *capacity;
+                let old_ptrs = (#(data.I.raw_ptr(),)*);
+
+                {
+                    // If any column fails, the columns resized so far are restored when the
+                    // partially constructed tuple is dropped.
+                    let resized = unsafe {
+                        // SAFETY: We guarantee that each data pointer is allocated to
+                        // capacity from self.alloc, and that no data past self.len is valid.
+                        (#(
+                            ResizeGuard::try_new(
+                                &mut data.I,
+                                old_capacity,
+                                new_capacity,
+                                1,
+                                &self.alloc,
+                                false,
+                            )?,
+                        )*)
+                    };
+                    #(resized.I.commit();)*
+                }
+
+                #(self.stats.on_resize(&data.I, old_ptrs.I, old_capacity, new_capacity);)*
+
+                *capacity = new_capacity;
+                Ok(())
+            }
+
+            /// Moves every row from the inline columns into new heap columns with room
+            /// for `capacity` rows.
+            ///
+            /// The heap columns are allocated before any row moves, so an error leaves
+            /// the vector exactly as it was.
+            fn try_spill(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+                // If any allocation fails, the blocks allocated so far are released
+                // when the partially constructed tuple is dropped.
+                let pending = (#(AllocGuard::<T~I, A>::try_new(capacity, 1, &self.alloc)?,)*);
+                let mut data = (#(pending.I.into_data(),)*);
+                let mut slice = self.raw_slice();
+
+                unsafe {
+                    // SAFETY: The heap columns are empty and have room for capacity > len
+                    // rows, and the inline rows are forgotten once the storage is replaced.
+                    Self::move_rows(slice.soa_mut_slices(), (#(data.I.raw_data(capacity),)*));
+                }
+
+                #(data.I.mark_init(0, self.len);)*
+                #(self.stats.on_relocate::<T~I>(0, capacity, 0);)*
+
+                self.storage = $storage::Heap { capacity, data };
+                Ok(())
+            }
+
+            /// Moves every row from the heap columns back inline, and releases them.
+            ///
+            /// The caller guarantees that the rows fit inline, so this can't fail.
+            fn unspill(&mut self) {
+                debug_assert!(self.len <= INLINE);
+
+                let $storage::Heap { capacity, data } = &mut self.storage else {
+                    return;
+                };
+
+                let capacity = *capacity;
+                let mut columns = (#([const { MaybeUninit::uninit() }; INLINE],)*);
+
+                unsafe {
+                    // SAFETY: The inline columns have room for INLINE >= len rows, and the
+                    // heap rows are forgotten once their blocks are released below.
+                    Self::move_rows((#(data.I.slice_mut(0, self.len),)*), (#(&mut columns.I[..],)*));
+                }
+
+                let _allocs = unsafe {
+                    // SAFETY: We guarantee that each data pointer is allocated to capacity
+                    // from self.alloc, and every row in it has just been moved out.
+                    (#(AllocGuard::new(&data.I, capacity, 1, &self.alloc),)*)
+                };
+
+                #(self.stats.on_relocate::<T~I>(capacity, 0, 0);)*
+
+                self.storage = $storage::Inline(UnsafeCell::new(columns));
+            }
+
+            /// Panics if the capacity is locked and debug assertions are enabled.
+            #[inline(always)]
+            fn assert_unlocked(&self, op: &str) {
+                if cfg!(debug_assertions) && self.capacity_locked {
+                    panic!(
+                        "{}::{op}: reallocation while the capacity is locked \
+                         (len {}, capacity {})",
+                        stringify!($small),
+                        self.len,
+                        self.capacity(),
+                    );
+                }
+            }
+
+            /// Returns an error if the capacity is locked and debug assertions are enabled.
+            #[inline(always)]
+            fn check_unlocked(&self) -> Result<(), TryReserveError> {
+                match cfg!(debug_assertions) && self.capacity_locked {
+                    true => Err(TryReserveError::CapacityLocked),
+                    false => Ok(()),
+                }
+            }
+
+            /// Copies every row of `from` into the start of `to`.
+            ///
+            /// # Safety
+            ///
+            /// It is up to the caller to guarantee that `to` has room for every row, and
+            /// that the rows in `from` are never used or dropped again.
+            #[inline]
+            unsafe fn move_rows(from: (#(&mut [T~I],)*), to: (#(&mut [MaybeUninit<T~I>],)*)) {
+                #(
+                    debug_assert!(from.I.len() <= to.I.len());
+
+                    unsafe {
+                        // SAFETY: The two columns are distinct allocations, and the caller
+                        // guarantees that `to` has room for every row.
+                        ptr::copy_nonoverlapping(from.I.as_ptr(), to.I.as_mut_ptr().cast::<T~I>(), from.I.len());
+                    }
+                )*
+            }
+        }
+
+        impl<const INLINE: usize, #(T~I: Sized,)*> Default for $small<INLINE, #(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<const INLINE: usize, #(T~I: Sized,)* A: Allocator> Drop for $small<INLINE, #(T~I,)* A> {
+            #[inline]
+            fn drop(&mut self) {
+                let slice = self.raw_slice();
+                let len = mem::replace(&mut self.len, 0);
+
+                // Guards are dropped in reverse order, so the elements are dropped first
+                // and the heap columns are always released, even if a destructor panics.
+                let _allocs = match &self.storage {
+                    $storage::Inline(_) => None,
+                    $storage::Heap { capacity, data } => Some(unsafe {
+                        // SAFETY: We guarantee that each data pointer is allocated to
+                        // capacity from self.alloc.
+                        (#(AllocGuard::new(&data.I, *capacity, 1, &self.alloc),)*)
+                    }),
+                };
+                let _elements = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len.
+                    (#(DropGuard::new(&slice.data.I, len),)*)
+                };
+            }
+        }
+    });}
+}
+
+seq!(N in 2..=12 {
+    declare_small_n!(
+        SmallVecSoa~N,
+        SmallStorage~N,
+        SliceSoa~N,
+        SliceSoaRef~N,
+        SliceSoaRefMut~N,
+        N
+    );
+});
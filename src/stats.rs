@@ -0,0 +1,128 @@
+//! Allocation counters for profiling how often a table's columns are resized.
+//!
+//! With the `stats` feature enabled, every `VecSoaN`, `PackedVecSoaN` and
+//! `SmallVecSoaN` counts the allocations, reallocations and deallocations of its
+//! columns, along with the number of bytes that had to be copied when a column moved
+//! to a new block. Without the feature, the counters are zero-sized and every update
+//! compiles to nothing.
+
+/// A snapshot of a vector's allocation counters.
+///
+/// Every column is counted separately, so growing a vector of three columns counts
+/// as three reallocations. Zero-sized columns never allocate and are never counted.
+/// A packed vector keeps every column in a single block, which is counted once, and
+/// a small vector only counts its columns while they are on the heap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// The number of column blocks allocated from scratch.
+    pub allocs: u64,
+    /// The number of column blocks resized in place or moved into a new block.
+    pub reallocs: u64,
+    /// The number of column blocks released while the vector was still alive.
+    pub deallocs: u64,
+    /// The number of bytes copied from an old block into a new one, including the
+    /// copies made by the allocator when it can't resize a block in place.
+    pub bytes_moved: u64,
+}
+
+#[cfg(feature = "stats")]
+mod imp {
+    use core::mem::{self, MaybeUninit};
+    use core::ptr::NonNull;
+
+    use super::*;
+    use crate::data::RawDataPtr;
+
+    /// Running allocation counters for a single vector.
+    #[derive(Default)]
+    pub(crate) struct StatsCounter {
+        stats: AllocStats,
+    }
+
+    impl StatsCounter {
+        #[inline(always)]
+        pub(crate) fn get(&self) -> AllocStats {
+            self.stats
+        }
+
+        #[inline(always)]
+        pub(crate) fn reset(&mut self) {
+            self.stats = AllocStats::default();
+        }
+
+        /// Records an in-place resize of `data` from `old_capacity` to `capacity`,
+        /// where `old_ptr` is where the column was before the resize.
+        #[inline]
+        pub(crate) fn on_resize<T>(
+            &mut self,
+            data: &RawDataPtr<T>,
+            old_ptr: NonNull<MaybeUninit<T>>,
+            old_capacity: usize,
+            capacity: usize,
+        ) {
+            // The allocator copies the whole old block when it can't resize in place.
+            let moved = match data.points_to(old_ptr) {
+                true => 0,
+                false => usize::min(old_capacity, capacity),
+            };
+            self.on_relocate::<T>(old_capacity, capacity, moved);
+        }
+
+        /// Records a column of `T` moving from a block of `old_capacity` elements to a
+        /// newly allocated block of `capacity` elements, copying `len` elements.
+        #[inline]
+        pub(crate) fn on_relocate<T>(&mut self, old_capacity: usize, capacity: usize, len: usize) {
+            // None of these can overflow, since each one is the size of an allocation.
+            let size = mem::size_of::<T>();
+            self.on_relocate_bytes(old_capacity * size, capacity * size, len * size);
+        }
+
+        /// Records a block of `old_size` bytes moving to a newly allocated block of
+        /// `size` bytes, copying `moved` bytes. Empty blocks are never allocated.
+        #[inline]
+        pub(crate) fn on_relocate_bytes(&mut self, old_size: usize, size: usize, moved: usize) {
+            match (old_size, size) {
+                (0, 0) => {}
+                (0, _) => self.stats.allocs += 1,
+                (_, 0) => self.stats.deallocs += 1,
+                _ => {
+                    self.stats.reallocs += 1;
+                    self.stats.bytes_moved += moved as u64;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+mod imp {
+    use core::mem::MaybeUninit;
+    use core::ptr::NonNull;
+
+    use crate::data::RawDataPtr;
+
+    /// Zero-sized stand-in for the `stats` feature's counters. Every update is a no-op.
+    #[derive(Default)]
+    pub(crate) struct StatsCounter;
+
+    #[allow(unused_variables)]
+    impl StatsCounter {
+        #[inline(always)]
+        pub(crate) fn on_resize<T>(
+            &mut self,
+            data: &RawDataPtr<T>,
+            old_ptr: NonNull<MaybeUninit<T>>,
+            old_capacity: usize,
+            capacity: usize,
+        ) {
+        }
+
+        #[inline(always)]
+        pub(crate) fn on_relocate<T>(&mut self, old_capacity: usize, capacity: usize, len: usize) {}
+
+        #[inline(always)]
+        pub(crate) fn on_relocate_bytes(&mut self, old_size: usize, size: usize, moved: usize) {}
+    }
+}
+
+pub(crate) use imp::StatsCounter;
@@ -1,13 +1,28 @@
+use core::mem::{self, MaybeUninit};
+
+use allocator_api2::alloc::{Allocator, Global};
 use seq_macro::seq;
 
-use crate::data::RawDataPtr;
+use crate::data::{handle_reserve_error, AllocGuard, DropGuard, RawDataPtr, ResizeGuard};
+use crate::error::TryReserveError;
+use crate::growth::GrowthPolicy;
+use crate::layout::ColumnLayout;
 use crate::slice::*;
+#[cfg(feature = "stats")]
+use crate::stats::AllocStats;
+use crate::stats::StatsCounter;
+use crate::zeroable::Zeroable;
 
 macro_rules! declare_vec_n {
     ($vec:ident, $slice:ident, $n:literal) => { seq!(I in 0..$n {
-        pub struct $vec<#(T~I: Sized,)*> {
+        pub struct $vec<#(T~I: Sized,)* A: Allocator = Global> {
             pub(crate) capacity: usize,
+            pub(crate) growth: GrowthPolicy,
+            pub(crate) layout: ColumnLayout,
+            pub(crate) capacity_locked: bool,
+            pub(crate) stats: StatsCounter,
             pub(crate) slice: $slice<#(T~I,)*>,
+            pub(crate) alloc: A,
         }
 
         impl<#(T~I: Sized,)*> $vec<#(T~I,)*> {
@@ -18,16 +33,239 @@ macro_rules! declare_vec_n {
 
             #[inline]
             pub fn with_capacity(capacity: usize) -> Self {
+                Self::with_capacity_in(capacity, Global)
+            }
+
+            /// Creates a new vector with the given capacity in every column.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+                Self::try_with_capacity_in(capacity, Global)
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> $vec<#(T~I,)* A> {
+            /// Creates a new, empty vector that allocates every column from `alloc`.
+            #[inline]
+            pub fn new_in(alloc: A) -> Self {
+                Self::with_capacity_in(0, alloc)
+            }
+
+            /// Creates a new vector with the given capacity in every column, allocating
+            /// every column from `alloc`.
+            #[inline]
+            pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+                let data = (#(RawDataPtr::with_capacity_in(capacity, 1, &alloc),)*);
+                let mut stats = StatsCounter::default();
+                #(stats.on_relocate::<T~I>(0, capacity, 0);)*
+
                 Self {
                     capacity,
-                    slice: $slice {
-                        len: 0,
-                        data: (#(RawDataPtr::with_capacity(capacity),)*),
+                    growth: GrowthPolicy::default(),
+                    layout: ColumnLayout::NATURAL,
+                    capacity_locked: false,
+                    stats,
+                    slice: $slice { len: 0, data },
+                    alloc,
+                }
+            }
+
+            /// Creates a new vector with the given capacity in every column, allocating
+            /// every column from `alloc`.
+            ///
+            /// Returns an error instead of panicking or aborting if the capacity
+            /// overflows or if the allocator reports a failure.
+            #[inline]
+            pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+                let mut vec = Self::new_in(alloc);
+                vec.try_reserve_exact(capacity)?;
+                Ok(vec)
+            }
+
+            /// Returns a reference to the allocator shared by every column.
+            #[inline(always)]
+            pub fn allocator(&self) -> &A {
+                &self.alloc
+            }
+
+            /// Returns the number of bytes allocated for each column.
+            #[inline]
+            pub fn per_column_bytes(&self) -> [usize; $n] {
+                // This can't overflow, since every column is allocated to this size.
+                [#(mem::size_of::<T~I>() * self.capacity,)*]
+            }
+
+            /// Returns the total number of bytes allocated across every column.
+            #[inline]
+            pub fn heap_bytes(&self) -> usize {
+                self.per_column_bytes().into_iter().sum()
+            }
+
+            /// Returns the number of allocated bytes across every column that don't hold
+            /// a row, i.e. the spare capacity in bytes.
+            #[inline]
+            pub fn wasted_bytes(&self) -> usize {
+                let spare = self.capacity - self.slice.len;
+                [#(mem::size_of::<T~I>() * spare,)*].into_iter().sum()
+            }
+
+            /// Resizes every column to `new_capacity` elements in place where the allocator
+            /// can, with any new elements zero-filled if `zeroed` is set.
+            ///
+            /// If any column fails to resize, the columns resized so far are restored to
+            /// the old capacity, so an error leaves the vector exactly as it was. Callers
+            /// check the capacity lock themselves, so that it names the right operation.
+            fn try_resize(
+                &mut self,
+                new_capacity: usize,
+                zeroed: bool,
+            ) -> Result<(), TryReserveError> {
+                debug_assert!(new_capacity >= self.slice.len);
+
+                let old_capacity = self.capacity;
+                let align = self.layout.align();
+                let old_ptrs = (#(self.slice.data.I.raw_ptr(),)*);
+
+                {
+                    // If any column fails, the columns resized so far are restored when the
+                    // partially constructed tuple is dropped.
+                    let resized = unsafe {
+                        // SAFETY: We guarantee that each data pointer is allocated to
+                        // self.capacity from self.alloc, and that no data past self.len is valid.
+                        (#(
+                            ResizeGuard::try_new(
+                                &mut self.slice.data.I,
+                                old_capacity,
+                                new_capacity,
+                                align,
+                                &self.alloc,
+                                zeroed,
+                            )?,
+                        )*)
+                    };
+                    #(resized.I.commit();)*
+                }
+
+                #(self.stats.on_resize(&self.slice.data.I, old_ptrs.I, old_capacity, new_capacity);)*
+
+                self.capacity = new_capacity;
+                Ok(())
+            }
+
+            /// Moves every column into a new allocation of `new_capacity` elements
+            /// with the given column layout, zero-filled if `zeroed` is set.
+            ///
+            /// All new blocks are allocated before any old block is released, so an
+            /// error partway through leaves the vector exactly as it was. Callers check
+            /// the capacity lock themselves, so that it is reported the right way.
+            fn try_relocate(
+                &mut self,
+                new_capacity: usize,
+                layout: ColumnLayout,
+                zeroed: bool,
+            ) -> Result<(), TryReserveError> {
+                debug_assert!(new_capacity >= self.slice.len);
+
+                // If any allocation fails, the blocks allocated so far are released
+                // when the partially constructed tuple is dropped.
+                let pending = (#(
+                    match zeroed {
+                        true => AllocGuard::<T~I, A>::try_new_zeroed(new_capacity, layout.align(), &self.alloc)?,
+                        false => AllocGuard::<T~I, A>::try_new(new_capacity, layout.align(), &self.alloc)?,
                     },
+                )*);
+
+                let old_align = self.layout.align();
+
+                unsafe {
+                    // SAFETY: We guarantee that each data pointer is allocated to
+                    // self.capacity and that the data in 0..self.len is valid.
+                    #(pending.I.commit(&mut self.slice.data.I, self.capacity, old_align, self.slice.len);)*
+                }
+
+                #(self.stats.on_relocate::<T~I>(self.capacity, new_capacity, self.slice.len);)*
 
+                self.capacity = new_capacity;
+                self.layout = layout;
+                Ok(())
+            }
+        }
+
+        impl<#(T~I: Zeroable,)*> $vec<#(T~I,)*> {
+            /// Creates a new vector of `len` rows where every element is zeroed.
+            ///
+            /// The columns are allocated with zeroed memory directly from the allocator,
+            /// which can often provide it without writing to every byte.
+            #[inline]
+            pub fn with_len_zeroed(len: usize) -> Self {
+                Self::with_len_zeroed_in(len, Global)
+            }
+        }
+
+        impl<#(T~I: Zeroable,)* A: Allocator> $vec<#(T~I,)* A> {
+            /// Creates a new vector of `len` rows where every element is zeroed,
+            /// allocating every column from `alloc`.
+            ///
+            /// See [`with_len_zeroed`]($vec::with_len_zeroed) for details.
+            #[inline]
+            pub fn with_len_zeroed_in(len: usize, alloc: A) -> Self {
+                let data = (#(RawDataPtr::with_capacity_zeroed_in(len, 1, &alloc),)*);
+                #(data.I.mark_init(0, len);)*
+                let mut stats = StatsCounter::default();
+                #(stats.on_relocate::<T~I>(0, len, 0);)*
+
+                Self {
+                    capacity: len,
+                    growth: GrowthPolicy::default(),
+                    layout: ColumnLayout::NATURAL,
+                    capacity_locked: false,
+                    stats,
+                    slice: $slice { len, data },
+                    alloc,
                 }
             }
+        }
 
+        impl<#(T~I: Sized,)*> Default for $vec<#(T~I,)*> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<#(T~I: Sized,)* A: Allocator> Drop for $vec<#(T~I,)* A> {
+            #[inline]
+            fn drop(&mut self) {
+                let len = mem::replace(&mut self.slice.len, 0);
+
+                // Guards are dropped in reverse order, so the elements are dropped first
+                // and the allocations are always released, even if a destructor panics.
+                let _allocs = unsafe {
+                    // SAFETY: We guarantee that each data pointer is allocated to
+                    // self.capacity from self.alloc.
+                    (#(AllocGuard::new(&self.slice.data.I, self.capacity, self.layout.align(), &self.alloc),)*)
+                };
+                let _elements = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len.
+                    (#(DropGuard::new(&self.slice.data.I, len),)*)
+                };
+            }
+        }
+    });}
+}
+
+// Implements every operation that a growable vector shares regardless of how its
+// columns are stored. The vector must have the fields `capacity`, `growth`, `layout`,
+// `capacity_locked`, `stats` and `slice`, with every column allocated to `capacity`
+// elements. It must also have a `try_resize(new_capacity, zeroed)` method that resizes
+// every column transactionally, and a `try_relocate(new_capacity, layout, zeroed)`
+// method that does the same while changing the column layout. Only those methods and
+// the drop differ.
+macro_rules! impl_growable_n {
+    ($vec:ident, $n:literal) => { seq!(I in 0..$n {
+        impl<#(T~I: Sized,)* A: Allocator> $vec<#(T~I,)* A> {
             #[inline(always)]
             pub fn is_empty(&self) -> bool {
                 self.slice.is_empty()
@@ -43,6 +281,179 @@ macro_rules! declare_vec_n {
                 self.capacity
             }
 
+            /// Locks the capacity, so that any operation that would reallocate a column
+            /// panics instead. This is useful to enforce in tests that code meant for
+            /// real-time threads never allocates.
+            ///
+            /// The lock is only enforced with debug assertions enabled. In release builds,
+            /// this has no effect on any other operation.
+            #[inline(always)]
+            pub fn lock_capacity(&mut self) {
+                self.capacity_locked = true;
+            }
+
+            /// Unlocks the capacity after a call to [`lock_capacity`](Self::lock_capacity).
+            #[inline(always)]
+            pub fn unlock_capacity(&mut self) {
+                self.capacity_locked = false;
+            }
+
+            /// Returns whether the capacity is locked with [`lock_capacity`](Self::lock_capacity).
+            #[inline(always)]
+            pub fn is_capacity_locked(&self) -> bool {
+                self.capacity_locked
+            }
+
+            /// Returns the allocation counters for this vector's columns.
+            #[cfg(feature = "stats")]
+            #[inline]
+            pub fn alloc_stats(&self) -> AllocStats {
+                self.stats.get()
+            }
+
+            /// Resets the allocation counters for this vector's columns to zero.
+            #[cfg(feature = "stats")]
+            #[inline]
+            pub fn reset_alloc_stats(&mut self) {
+                self.stats.reset();
+            }
+
+            /// Returns the policy used to compute new capacities when growing.
+            #[inline(always)]
+            pub fn growth_policy(&self) -> GrowthPolicy {
+                self.growth
+            }
+
+            /// Sets the policy used to compute new capacities when growing.
+            #[inline(always)]
+            pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+                self.growth = growth;
+            }
+
+            /// Returns this vector with the given growth policy.
+            #[inline(always)]
+            pub fn with_growth_policy(mut self, growth: GrowthPolicy) -> Self {
+                self.growth = growth;
+                self
+            }
+
+            /// Returns the alignment and capacity padding used for every column.
+            #[inline(always)]
+            pub fn column_layout(&self) -> ColumnLayout {
+                self.layout
+            }
+
+            /// Returns this vector with the given column alignment and capacity padding.
+            ///
+            /// If this vector has already allocated, every column is moved into a new
+            /// allocation with the new alignment, and the capacity is padded to match.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if there is not enough memory to perform the new
+            /// allocation, or if the resulting allocation size is greater than `isize::MAX`.
+            #[inline]
+            pub fn with_column_layout(mut self, layout: ColumnLayout) -> Self {
+                self.assert_unlocked("with_column_layout");
+
+                let result = match layout.pad(self.capacity) {
+                    Some(capacity) => self.try_relocate(capacity, layout, false),
+                    None => Err(TryReserveError::CapacityOverflow),
+                };
+
+                if let Err(err) = result {
+                    handle_reserve_error(err);
+                }
+
+                self
+            }
+
+            /// Returns the length rounded up to the next multiple of the column layout's
+            /// lane count. This is never greater than the capacity.
+            #[inline(always)]
+            pub fn padded_len(&self) -> usize {
+                // This can't overflow, since the capacity is also a multiple of the lanes.
+                self.slice.len.next_multiple_of(self.layout.lanes())
+            }
+
+            /// Returns the padded tail of each column as `MaybeUninit<T>` slices.
+            ///
+            /// Each returned slice covers the range `self.len()..self.padded_len()` of its
+            /// column. SIMD kernels can fill this tail (for example with zeroes or
+            /// identity values) so that their final lane is safe to process in full.
+            #[inline]
+            pub fn padded_tail_mut(&mut self) -> (#(&mut [MaybeUninit<T~I>],)*) {
+                let len = self.slice.len;
+                let padded_len = self.padded_len();
+
+                unsafe {
+                    // SAFETY: We guarantee that each data pointer is allocated to self.capacity,
+                    // and padded_len <= self.capacity since the capacity is padded too.
+                    (#(&mut self.slice.data.I.raw_data(padded_len)[len..],)*)
+                }
+            }
+
+            /// Forces the length of the vector to `new_len`.
+            ///
+            /// This is intended for use with [`spare_capacity_mut`](Self::spare_capacity_mut)
+            /// after initializing the spare capacity of every column in place.
+            ///
+            /// # Safety
+            ///
+            /// It is up to the caller to guarantee the following:
+            /// - `new_len <= self.capacity()`
+            /// - All elements in the range `self.len()..new_len` are valid in every column
+            #[inline(always)]
+            pub unsafe fn set_len(&mut self, new_len: usize) {
+                debug_assert!(new_len <= self.capacity);
+                let len = self.slice.len;
+
+                if new_len > len {
+                    #(self.slice.data.I.mark_init(len, new_len - len);)*
+                } else {
+                    #(self.slice.data.I.mark_uninit(new_len, len - new_len);)*
+                }
+
+                self.slice.len = new_len;
+            }
+
+            /// Checks that all of this vector's invariants hold.
+            ///
+            /// With the `checked` feature enabled, this also checks that every column is
+            /// allocated to exactly `self.capacity()` elements and that exactly the first
+            /// `self.len()` elements of every column are initialized.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic with a description of the first violated invariant.
+            #[inline]
+            pub fn validate(&self) {
+                assert!(
+                    self.slice.len <= self.capacity,
+                    "{}::validate: len {} exceeds capacity {}",
+                    stringify!($vec),
+                    self.slice.len,
+                    self.capacity,
+                );
+
+                #(self.slice.data.I.validate(self.capacity, self.slice.len);)*
+            }
+
+            /// Returns the remaining spare capacity of each column as `MaybeUninit<T>` slices.
+            ///
+            /// Each returned slice covers the range `self.len()..self.capacity()` of its
+            /// column. Once the data has been written, use [`set_len`](Self::set_len) to
+            /// mark it as initialized.
+            #[inline]
+            pub fn spare_capacity_mut(&mut self) -> (#(&mut [MaybeUninit<T~I>],)*) {
+                let len = self.slice.len;
+
+                unsafe {
+                    // SAFETY: We guarantee that each data pointer is allocated to self.capacity.
+                    (#(&mut self.slice.data.I.raw_data(self.capacity)[len..],)*)
+                }
+            }
+
             #[inline]
             pub fn push(&mut self, data: (#(T~I,)*)) {
                 self.reserve(1);
@@ -55,59 +466,322 @@ macro_rules! declare_vec_n {
                 self.slice.len += 1;
             }
 
+            /// Appends a row to the back of the vector, returning an error if the
+            /// vector needed to grow and the allocation failed.
+            ///
+            /// On error, the vector is left unchanged and `data` is dropped.
+            #[inline]
+            pub fn try_push(&mut self, data: (#(T~I,)*)) -> Result<(), TryReserveError> {
+                self.try_reserve(1)?;
+
+                unsafe {
+                    // SAFETY: We guarantee that the data at self.len is allocated and invalid.
+                    #(self.slice.data.I.write(self.slice.len, data.I);)*
+                }
+
+                self.slice.len += 1;
+                Ok(())
+            }
+
+            /// Appends a row to the back of the vector if there is spare capacity for it,
+            /// returning the index of the new row.
+            ///
+            /// This never allocates. If the vector is full, the row is handed back as
+            /// the error.
+            #[inline]
+            pub fn push_within_capacity(&mut self, data: (#(T~I,)*)) -> Result<usize, (#(T~I,)*)> {
+                let index = self.slice.len;
+
+                if index == self.capacity {
+                    return Err(data);
+                }
+
+                unsafe {
+                    // SAFETY: We checked that the data at self.len is allocated, and we
+                    // guarantee that it is invalid.
+                    #(self.slice.data.I.write(index, data.I);)*
+                }
+
+                self.slice.len += 1;
+                Ok(index)
+            }
+
+            /// Inserts a row at `index` if there is spare capacity for it, shifting every
+            /// row after it up by one.
+            ///
+            /// This never allocates. If the vector is full, the row is handed back as
+            /// the error.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if `index > self.len()`.
+            #[inline]
+            pub fn insert_within_capacity(
+                &mut self,
+                index: usize,
+                data: (#(T~I,)*),
+            ) -> Result<(), (#(T~I,)*)> {
+                let len = self.slice.len;
+                assert!(index <= len, "insertion index {index} is out of bounds for len {len}");
+
+                if len == self.capacity {
+                    return Err(data);
+                }
+
+                unsafe {
+                    // SAFETY: We checked that there is room for len + 1 elements and that
+                    // the index is within bounds, and the data in 0..len is valid.
+                    #(self.slice.data.I.insert(index, len, data.I);)*
+                }
+
+                self.slice.len += 1;
+                Ok(())
+            }
+
             #[inline]
             pub fn reserve(&mut self, len: usize) {
-                if (self.slice.len + len) > self.capacity {
-                    let new_capacity = usize::max(self.capacity * 2, self.slice.len + len);
+                let required = self.slice.len.checked_add(len).expect("capacity overflow");
+
+                if required > self.capacity {
+                    self.assert_unlocked("reserve");
+                    let new_capacity = self.next_capacity(required).expect("capacity overflow");
 
-                    unsafe {
-                        // SAFETY: We guarantee that the current data is valid up to capacity.
-                        // and that new_capacity is greater than the old self.capacity value.
-                        #(self.slice.data.I.grow(self.capacity, new_capacity);)*
+                    if let Err(err) = self.try_resize(new_capacity, false) {
+                        handle_reserve_error(err);
                     }
+                }
+            }
+
+            /// Reserves capacity for exactly `len` more rows, without over-allocating
+            /// according to the growth policy.
+            #[inline]
+            pub fn reserve_exact(&mut self, len: usize) {
+                let required = self.slice.len.checked_add(len).expect("capacity overflow");
+
+                if required > self.capacity {
+                    self.assert_unlocked("reserve_exact");
+                    let new_capacity = self.layout.pad(required).expect("capacity overflow");
 
-                    self.capacity = new_capacity;
+                    if let Err(err) = self.try_resize(new_capacity, false) {
+                        handle_reserve_error(err);
+                    }
+                }
+            }
+
+            /// Shrinks the capacity of every column as much as possible.
+            #[inline]
+            pub fn shrink_to_fit(&mut self) {
+                self.shrink_to(0);
+            }
+
+            /// Shrinks the capacity of every column with a lower bound.
+            ///
+            /// The capacity will remain at least as large as both the length and
+            /// `min_capacity`, padded according to the column layout. If the current
+            /// capacity is already lower, this does nothing.
+            #[inline]
+            pub fn shrink_to(&mut self, min_capacity: usize) {
+                let new_capacity = usize::max(self.slice.len, min_capacity);
+                // Saturating keeps the current (already padded) capacity on overflow.
+                let new_capacity = self.layout.pad(new_capacity).unwrap_or(usize::MAX);
+
+                if new_capacity < self.capacity {
+                    self.assert_unlocked("shrink_to");
+
+                    if let Err(err) = self.try_resize(new_capacity, false) {
+                        handle_reserve_error(err);
+                    }
+                }
+            }
+
+            /// Tries to reserve capacity for at least `len` more rows.
+            ///
+            /// Growth is transactional across columns: if any column fails to allocate,
+            /// every column is left with its original allocation and contents. With
+            /// debug assertions enabled, this returns
+            /// [`CapacityLocked`](TryReserveError::CapacityLocked) instead of growing
+            /// while the capacity is locked.
+            #[inline]
+            pub fn try_reserve(&mut self, len: usize) -> Result<(), TryReserveError> {
+                let required = self
+                    .slice
+                    .len
+                    .checked_add(len)
+                    .ok_or(TryReserveError::CapacityOverflow)?;
+
+                if required > self.capacity {
+                    self.check_unlocked()?;
+                    let new_capacity = self
+                        .next_capacity(required)
+                        .ok_or(TryReserveError::CapacityOverflow)?;
+                    self.try_resize(new_capacity, false)?;
+                }
+
+                Ok(())
+            }
+
+            /// Tries to reserve capacity for exactly `len` more rows.
+            ///
+            /// Growth is transactional across columns, and a locked capacity is reported,
+            /// as in [`try_reserve`](Self::try_reserve).
+            #[inline]
+            pub fn try_reserve_exact(&mut self, len: usize) -> Result<(), TryReserveError> {
+                let required = self
+                    .slice
+                    .len
+                    .checked_add(len)
+                    .ok_or(TryReserveError::CapacityOverflow)?;
+
+                if required > self.capacity {
+                    self.check_unlocked()?;
+                    let new_capacity = self
+                        .layout
+                        .pad(required)
+                        .ok_or(TryReserveError::CapacityOverflow)?;
+                    self.try_resize(new_capacity, false)?;
+                }
+
+                Ok(())
+            }
+
+            /// Computes the next capacity to grow to according to the growth policy,
+            /// padded according to the column layout.
+            #[inline]
+            fn next_capacity(&self, required: usize) -> Option<usize> {
+                let elem_size = [#(mem::size_of::<T~I>(),)*].into_iter().max().unwrap_or(0);
+                let new_capacity = self.growth.next_capacity(self.capacity, required, elem_size);
+                self.layout.pad(new_capacity)
+            }
+
+            /// Panics if the capacity is locked and debug assertions are enabled.
+            #[inline(always)]
+            fn assert_unlocked(&self, op: &str) {
+                if cfg!(debug_assertions) && self.capacity_locked {
+                    panic!(
+                        "{}::{op}: reallocation while the capacity is locked \
+                         (len {}, capacity {})",
+                        stringify!($vec),
+                        self.slice.len,
+                        self.capacity,
+                    );
+                }
+            }
+
+            /// Returns an error if the capacity is locked and debug assertions are enabled.
+            #[inline(always)]
+            pub(crate) fn check_unlocked(&self) -> Result<(), TryReserveError> {
+                match cfg!(debug_assertions) && self.capacity_locked {
+                    true => Err(TryReserveError::CapacityLocked),
+                    false => Ok(()),
                 }
             }
 
             #[inline]
             pub fn clear(&mut self) {
-                unsafe {
-                    // SAFETY: We guarantee all of our data is valid in the range 0..self.len.
-                    #(self.slice.data.I.drop_to(self.slice.len);)*
-                    self.slice.len = 0;
+                // Set the length first, so that a panicking destructor can never cause
+                // any element to be observed or dropped again after this call.
+                let len = mem::replace(&mut self.slice.len, 0);
+
+                let _guards = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range 0..len,
+                    // and nothing else can access that data now that self.len is 0.
+                    // If one column's destructor panics, unwinding still drops the
+                    // guards (and so the data) for every other column.
+                    (#(DropGuard::new(&self.slice.data.I, len),)*)
+                };
+            }
+
+            /// Shortens this vector to `len` rows, dropping the rest.
+            ///
+            /// If `len` is greater than or equal to the current length, this has no effect.
+            /// This does not change the capacity.
+            #[inline]
+            pub fn truncate(&mut self, len: usize) {
+                let old_len = self.slice.len;
+                if len >= old_len {
+                    return;
                 }
+
+                // Set the length first, as in clear, in case a destructor panics.
+                self.slice.len = len;
+
+                let _guards = unsafe {
+                    // SAFETY: We guarantee all of our data is valid in the range
+                    // 0..old_len, and nothing else can access the data in len..old_len
+                    // now that self.len is len.
+                    (#(DropGuard::new(&self.slice.data.I.offset(len), old_len - len),)*)
+                };
             }
 
             #[inline]
             pub fn swap_remove(&mut self, index: usize) -> (#(T~I,)*) {
                 assert!(index < self.slice.len);
 
-                let result = unsafe {
-                    // SAFETY: We guarantee that the data in the range 0..self.len is valid,
-                    // and we check that the index is within bounds using the assert above.
-                    (#(self.slice.data.I.swap_remove(index, self.slice.len),)*)
-                };
-
+                let len = self.slice.len;
                 self.slice.len -= 1;
-                result
+
+                unsafe {
+                    // SAFETY: We guarantee that the data in the range 0..len is valid,
+                    // and we check that the index is within bounds using the assert above.
+                    // Moving the elements out can't panic, and from here on the caller
+                    // owns them, so dropping them can never affect this vector.
+                    (#(self.slice.data.I.swap_remove(index, len),)*)
+                }
             }
         }
 
-        impl<#(T~I: Sized,)*> Drop for $vec<#(T~I,)*> {
+        impl<#(T~I: Zeroable,)* A: Allocator> $vec<#(T~I,)* A> {
+            /// Resizes the vector to `new_len` rows, filling any new rows with zeroes.
+            ///
+            /// If the vector grows past its capacity, the new memory is requested zeroed
+            /// from the allocator, so only spare capacity that was already allocated has
+            /// to be cleared by hand. If `new_len` is less than the current length, the
+            /// excess rows are dropped.
+            ///
+            /// # Panics
+            ///
+            /// This operation will panic if there is not enough memory to perform the new
+            /// allocation, or if the resulting allocation size is greater than `isize::MAX`.
             #[inline]
-            fn drop(&mut self) {
-                self.clear();
+            pub fn resize_zeroed(&mut self, new_len: usize) {
+                let len = self.slice.len;
+
+                if new_len <= len {
+                    self.truncate(new_len);
+                    return;
+                }
+
+                // Only memory in len..zero_end may hold stale bytes after this point.
+                let mut zero_end = new_len;
+
+                if new_len > self.capacity {
+                    self.assert_unlocked("resize_zeroed");
+                    let old_capacity = self.capacity;
+                    let new_capacity = self.next_capacity(new_len).expect("capacity overflow");
+
+                    if let Err(err) = self.try_resize(new_capacity, true) {
+                        handle_reserve_error(err);
+                    }
+
+                    // Only the spare capacity that was already allocated can hold stale bytes.
+                    zero_end = usize::max(len, old_capacity);
+                }
 
                 unsafe {
-                    // SAFETY: We guarantee that each data pointer is allocated to self.capacity.
-                    #(self.slice.data.I.dealloc(self.capacity);)*
+                    // SAFETY: We guarantee that each data pointer is allocated to at least
+                    // new_len elements, and that no data past self.len is valid. Every
+                    // element in len..new_len is then zeroed, which is valid for Zeroable.
+                    #(self.slice.data.I.write_zeroed(len, zero_end - len);)*
+                    self.set_len(new_len);
                 }
             }
         }
     });}
 }
 
+pub(crate) use impl_growable_n;
+
 seq!(N in 2..=12 {
     declare_vec_n!(VecSoa~N, SliceSoa~N, N);
+    impl_growable_n!(VecSoa~N, N);
 });
@@ -0,0 +1,63 @@
+//! Marker trait for types where the all-zero bit pattern is a valid value.
+//!
+//! Vectors whose columns are all [`Zeroable`] can be created and grown with zero-filled
+//! memory straight from the allocator (see `with_len_zeroed` and `resize_zeroed`),
+//! instead of writing every row through `push`.
+//!
+//! With the `bytemuck` feature enabled, this is a re-export of `bytemuck::Zeroable`,
+//! so types that derive it from bytemuck work here directly. Prefer implementing
+//! bytemuck's trait in that case, since the two are the same trait.
+
+#[cfg(feature = "bytemuck")]
+pub use bytemuck::Zeroable;
+
+#[cfg(not(feature = "bytemuck"))]
+pub use imp::Zeroable;
+
+#[cfg(not(feature = "bytemuck"))]
+mod imp {
+    use core::marker::PhantomData;
+    use core::mem::MaybeUninit;
+    use core::num::*;
+
+    /// Types for which a value made of all zero bytes is valid.
+    ///
+    /// # Safety
+    ///
+    /// It is up to the implementer to guarantee that the all-zero bit pattern is a
+    /// valid, safe-to-use value of the type. For example, this holds for integers,
+    /// floats, and `Option<NonZeroU32>`, but not for references or `NonZeroU32`.
+    pub unsafe trait Zeroable: Sized {}
+
+    macro_rules! impl_zeroable {
+        ($($t:ty),* $(,)?) => {
+            $(unsafe impl Zeroable for $t {})*
+        };
+    }
+
+    impl_zeroable!(u8, u16, u32, u64, u128, usize);
+    impl_zeroable!(i8, i16, i32, i64, i128, isize);
+    impl_zeroable!(f32, f64, bool, char, ());
+
+    impl_zeroable!(
+        Option<NonZeroU8>,
+        Option<NonZeroU16>,
+        Option<NonZeroU32>,
+        Option<NonZeroU64>,
+        Option<NonZeroU128>,
+        Option<NonZeroUsize>,
+        Option<NonZeroI8>,
+        Option<NonZeroI16>,
+        Option<NonZeroI32>,
+        Option<NonZeroI64>,
+        Option<NonZeroI128>,
+        Option<NonZeroIsize>,
+    );
+
+    unsafe impl<T: Zeroable> Zeroable for Wrapping<T> {}
+    unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+    unsafe impl<T> Zeroable for MaybeUninit<T> {}
+    unsafe impl<T: ?Sized> Zeroable for PhantomData<T> {}
+    unsafe impl<T> Zeroable for *const T {}
+    unsafe impl<T> Zeroable for *mut T {}
+}
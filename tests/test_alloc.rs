@@ -0,0 +1,242 @@
+#![cfg(feature = "alloc")]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use std::alloc::Layout;
+use std::cell::{Cell, RefCell};
+use std::ptr::NonNull;
+
+use vec_soa::prelude::*;
+use vec_soa::{AllocError, Allocator, Global};
+
+#[derive(Default)]
+struct CountingAlloc {
+    allocs: Cell<usize>,
+    frees: Cell<usize>,
+    live_bytes: Cell<usize>,
+}
+
+unsafe impl Allocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() + layout.size());
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.frees.set(self.frees.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+pub fn test_new_in() {
+    let alloc = CountingAlloc::default();
+
+    {
+        let mut vec = VecSoa3::<u16, u32, u64, _>::new_in(&alloc);
+        assert_eq!(alloc.allocs.get(), 0);
+
+        vec.push((1, 2, 3));
+        assert_eq!(alloc.allocs.get(), 3);
+        assert_eq!(alloc.live_bytes.get(), 2 + 4 + 8);
+
+        for i in 0..10 {
+            vec.push((i, i as u32, i as u64));
+        }
+        vec.shrink_to_fit();
+        assert_eq!(alloc.live_bytes.get(), 11 * (2 + 4 + 8));
+
+        let (a, b, c) = vec.index(0);
+        assert_eq!((*a, *b, *c), (1, 2, 3));
+        assert_eq!(vec.iter().count(), 11);
+    }
+
+    assert_eq!(alloc.live_bytes.get(), 0);
+    assert_eq!(alloc.allocs.get(), alloc.frees.get());
+}
+
+#[test]
+pub fn test_with_capacity_in() {
+    let alloc = CountingAlloc::default();
+
+    {
+        let mut vec = VecSoa2::<u8, (), _>::with_capacity_in(8, &alloc);
+        assert_eq!(alloc.allocs.get(), 1);
+        assert_eq!(alloc.live_bytes.get(), 8);
+
+        vec.try_reserve_exact(16).unwrap();
+        assert_eq!(alloc.live_bytes.get(), 16);
+    }
+
+    assert_eq!(alloc.live_bytes.get(), 0);
+
+    let vec = VecSoa2::<u8, u16, _>::try_with_capacity_in(4, &alloc).unwrap();
+    assert_eq!(vec.capacity(), 4);
+    assert_eq!(alloc.live_bytes.get(), 4 + 8);
+}
+
+/// An allocator that counts how often blocks are resized rather than allocated.
+#[derive(Default)]
+struct ResizingAlloc {
+    allocs: Cell<usize>,
+    grows: Cell<usize>,
+    shrinks: Cell<usize>,
+}
+
+unsafe impl Allocator for ResizingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.grows.set(self.grows.get() + 1);
+        unsafe { Global.grow(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.shrinks.set(self.shrinks.get() + 1);
+        unsafe { Global.shrink(ptr, old_layout, new_layout) }
+    }
+}
+
+#[test]
+pub fn test_reserve_resizes_in_place() {
+    let alloc = ResizingAlloc::default();
+    let mut vec = VecSoa3::<u8, u32, u64, _>::with_capacity_in(4, &alloc);
+    for i in 0..4 {
+        vec.push((i, i as u32, i as u64));
+    }
+    assert_eq!(alloc.allocs.get(), 3);
+
+    // Growing and shrinking hand each column to the allocator to resize.
+    vec.reserve(100);
+    vec.try_reserve_exact(500).unwrap();
+    assert_eq!(alloc.grows.get(), 6);
+
+    vec.shrink_to_fit();
+    assert_eq!(alloc.shrinks.get(), 3);
+    assert_eq!(alloc.allocs.get(), 3);
+
+    assert_eq!(vec.capacity(), 4);
+    assert_eq!(vec.index(3), (&3, &3, &3));
+    vec.validate();
+}
+
+/// Blocks larger than this are only backed by this many real bytes, so that tests
+/// can request huge capacities without touching them.
+const REAL_LIMIT: usize = 1 << 16;
+
+/// An allocator that fails on a chosen allocation, and checks that every block is
+/// released with the layout it was allocated with.
+#[derive(Default)]
+struct FailingAlloc {
+    allocs: Cell<usize>,
+    fail_at: Cell<Option<usize>>,
+    live: RefCell<Vec<(NonNull<u8>, Layout)>>,
+}
+
+impl FailingAlloc {
+    fn real_layout(layout: Layout) -> Layout {
+        Layout::from_size_align(layout.size().min(REAL_LIMIT), layout.align()).unwrap()
+    }
+}
+
+unsafe impl Allocator for FailingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let index = self.allocs.get();
+        self.allocs.set(index + 1);
+
+        if self.fail_at.get() == Some(index) {
+            return Err(AllocError);
+        }
+
+        let ptr = Global.allocate(Self::real_layout(layout))?.cast::<u8>();
+        self.live.borrow_mut().push((ptr, layout));
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let mut live = self.live.borrow_mut();
+        let index = live
+            .iter()
+            .position(|&(live_ptr, _)| live_ptr == ptr)
+            .expect("deallocated a block that isn't live");
+
+        let (_, allocated) = live.swap_remove(index);
+        assert_eq!(
+            allocated, layout,
+            "block deallocated with a different layout"
+        );
+        unsafe { Global.deallocate(ptr, Self::real_layout(layout)) }
+    }
+}
+
+#[test]
+pub fn test_try_reserve_fails_on_last_column() {
+    let alloc = FailingAlloc::default();
+
+    {
+        let mut vec = VecSoa4::<u8, u16, u32, u64, _>::with_capacity_in(4, &alloc);
+        for i in 0..4 {
+            vec.push((i, i as u16, i as u32, i as u64));
+        }
+        assert_eq!(alloc.allocs.get(), 4);
+
+        // Columns 0..3 of the new allocation succeed, and column 3 fails.
+        alloc.fail_at.set(Some(alloc.allocs.get() + 3));
+        let result = vec.try_reserve(100);
+        assert!(matches!(result, Err(TryReserveError::AllocError { .. })));
+
+        assert_eq!(vec.capacity(), 4);
+        assert_eq!(alloc.live.borrow().len(), 4);
+        assert_eq!(vec.soa_slices().3, &[0, 1, 2, 3]);
+        vec.validate();
+
+        alloc.fail_at.set(None);
+        vec.push((4, 4, 4, 4));
+        assert_eq!(vec.index(4), (&4, &4, &4, &4));
+    }
+
+    assert!(alloc.live.borrow().is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "checked"))] // The checked tracker can't track a capacity this large.
+pub fn test_reserve_overflows_on_last_column() {
+    let alloc = FailingAlloc::default();
+
+    {
+        let mut vec = VecSoa4::<u8, u8, u8, u64, _>::new_in(&alloc);
+        vec.push((1, 2, 3, 4));
+
+        // The u8 columns fit, but the u64 column overflows isize::MAX bytes.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            vec.reserve_exact(isize::MAX as usize / 4);
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(vec.capacity(), 1);
+        assert_eq!(alloc.live.borrow().len(), 4);
+        assert_eq!(vec.index(0), (&1, &2, &3, &4));
+        vec.validate();
+    }
+
+    // Dropping the vector releases every column with the layout it had.
+    assert!(alloc.live.borrow().is_empty());
+}
@@ -0,0 +1,152 @@
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+use vec_soa::prelude::*;
+
+#[test]
+fn test_push_full() {
+    let mut vec = ArrayVecSoa3::<4, u32, String, u8>::new();
+    assert_eq!(vec.capacity(), 4);
+    assert_eq!(vec.remaining_capacity(), 4);
+
+    for i in 0..4 {
+        assert!(vec.push((i, i.to_string(), i as u8)).is_ok());
+    }
+
+    assert!(vec.is_full());
+    assert_eq!(vec.remaining_capacity(), 0);
+
+    let rejected = vec.push((9, "nine".to_string(), 9));
+    assert_eq!(rejected, Err((9, "nine".to_string(), 9)));
+    assert_eq!(vec.len(), 4);
+
+    let slice = vec.as_slice();
+    let (a, b, c) = slice.soa_slices();
+    assert_eq!(a, &[0, 1, 2, 3]);
+    assert_eq!(b, &["0", "1", "2", "3"]);
+    assert_eq!(c, &[0, 1, 2, 3]);
+
+    vec.validate();
+}
+
+#[test]
+fn test_slice_api() {
+    let mut vec = ArrayVecSoa2::<8, u32, f32>::new();
+    for i in 0..5 {
+        vec.push((i, i as f32)).unwrap();
+    }
+
+    let slice = vec.as_slice();
+    assert_eq!(slice.get(2), Some((&2, &2.0)));
+    assert_eq!(slice.get(5), None);
+    assert_eq!(slice.index(1..3).soa_slices().0, &[1, 2]);
+
+    for (a, b) in vec.as_mut_slice().iter_mut() {
+        *a *= 10;
+        *b *= 10.0;
+    }
+
+    assert_eq!(vec.as_slice().soa_slices().0, &[0, 10, 20, 30, 40]);
+    assert_eq!(vec.as_slice().iter().map(|(_, b)| *b).sum::<f32>(), 100.0);
+
+    assert_eq!(vec.swap_remove(0), (0, 0.0));
+    assert_eq!(vec.as_slice().soa_slices().0, &[40, 10, 20, 30]);
+}
+
+#[test]
+fn test_moves() {
+    let mut vec = ArrayVecSoa2::<4, u32, String>::new();
+    vec.push((1, "one".to_string())).unwrap();
+
+    // Moving the vector moves its columns along with it.
+    let mut boxed = Box::new(vec);
+    boxed.push((2, "two".to_string())).unwrap();
+    assert_eq!(boxed.as_slice().soa_slices().1, &["one", "two"]);
+
+    let mut vecs = [*boxed, ArrayVecSoa2::new()];
+
+    let vec = &mut vecs[0];
+    *vec.as_mut_slice().index_mut(1).0 = 20;
+    assert_eq!(vec.as_slice().soa_slices().0, &[1, 20]);
+
+    vec.validate();
+}
+
+#[test]
+fn test_swap_in_place() {
+    let mut a = ArrayVecSoa2::<4, u32, String>::new();
+    let mut b = ArrayVecSoa2::<4, u32, String>::new();
+    a.push((1, "one".to_string())).unwrap();
+    b.push((2, "two".to_string())).unwrap();
+    b.push((3, "three".to_string())).unwrap();
+
+    // Swapping moves the columns without going through any method of either vector.
+    std::mem::swap(&mut a, &mut b);
+    assert_eq!(a.as_slice().soa_slices().1, &["two", "three"]);
+    assert_eq!(b.as_slice().soa_slices().1, &["one"]);
+
+    a.push((4, "four".to_string())).unwrap();
+    assert_eq!(a.as_slice().soa_slices().0, &[2, 3, 4]);
+    a.validate();
+    b.validate();
+}
+
+#[test]
+fn test_sync() {
+    fn assert_sync<T: Sync>(_: &T) {}
+
+    let mut vec = ArrayVecSoa2::<4, u32, String>::new();
+    vec.push((1, "one".to_string())).unwrap();
+    assert_sync(&vec);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| assert_eq!(vec.as_slice().soa_slices().0, &[1]));
+        scope.spawn(|| assert_eq!(vec.len(), 1));
+    });
+}
+
+#[test]
+fn test_spare_capacity() {
+    let mut vec = ArrayVecSoa2::<4, u16, u64>::new();
+    vec.push((1, 1)).unwrap();
+
+    let (a, b) = vec.spare_capacity_mut();
+    assert_eq!(a.len(), 3);
+    a[0] = MaybeUninit::new(2);
+    b[0] = MaybeUninit::new(2);
+
+    unsafe { vec.set_len(2) };
+    assert_eq!(vec.as_slice().soa_slices(), (&[1, 2][..], &[1, 2][..]));
+
+    vec.validate();
+}
+
+#[test]
+fn test_drops() {
+    let rc = Rc::new(());
+
+    let mut vec = ArrayVecSoa2::<3, Rc<()>, u8>::new();
+    for _ in 0..3 {
+        vec.push((rc.clone(), 0)).unwrap();
+    }
+
+    // The rejected row is handed back rather than dropped.
+    let rejected = vec.push((rc.clone(), 0)).unwrap_err();
+    assert_eq!(Rc::strong_count(&rc), 5);
+    drop(rejected);
+
+    vec.clear();
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    vec.push((rc.clone(), 0)).unwrap();
+    drop(vec);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn test_zero_capacity() {
+    let mut vec = ArrayVecSoa2::<0, u32, u8>::new();
+    assert!(vec.is_full());
+    assert_eq!(vec.push((1, 1)), Err((1, 1)));
+    vec.validate();
+}
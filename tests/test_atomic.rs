@@ -0,0 +1,50 @@
+#![cfg(feature = "alloc")]
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use vec_soa::prelude::*;
+
+#[test]
+fn test_cell_columns() {
+    let mut vec = VecSoa3::new();
+    for i in 0..4u32 {
+        vec.push((i, i.to_string(), 0.0f32));
+    }
+
+    let (a, b, c) = vec.as_cell_columns();
+    for (a, c) in a.iter().zip(c) {
+        c.set(a.get() as f32 * 2.0);
+    }
+    a[0].swap(&a[3]);
+    b[1].replace("one".to_string());
+
+    assert_eq!(vec.soa_slices().0, &[3, 1, 2, 0]);
+    assert_eq!(vec.soa_slices().1[1], "one");
+    assert_eq!(vec.soa_slices().2, &[0.0, 2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn test_atomic_column_scatter_add() {
+    let mut vec = VecSoa3::new();
+    for i in 0..16u64 {
+        vec.push((i, 0u32, i as i16));
+    }
+
+    let counts = vec.as_atomic_column::<1>();
+    thread::scope(|scope| {
+        for t in 0..4 {
+            scope.spawn(move || {
+                for i in 0..1000 {
+                    counts[(i * 7 + t) % 16].fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    assert_eq!(vec.soa_slices().1.iter().sum::<u32>(), 4000);
+
+    vec.as_atomic_column::<2>()[5].store(-5, Ordering::Relaxed);
+    vec.as_atomic_column::<0>()[5].fetch_add(10, Ordering::Relaxed);
+    assert_eq!(vec.soa_slices().0[5], 15);
+    assert_eq!(vec.soa_slices().2[5], -5);
+}
@@ -1,3 +1,7 @@
+#![cfg(feature = "alloc")]
+// The casts to u64 are kept uniform across columns, including the u64 one.
+#![allow(clippy::unnecessary_cast)]
+
 use vec_soa::prelude::*;
 
 #[test]
@@ -188,3 +192,183 @@ pub fn test_reserve_massive() {
 pub fn test_with_capacity_massive() {
     let _ = VecSoa3::<u16, u32, u64>::with_capacity(isize::MAX as usize + 1);
 }
+
+#[test]
+pub fn test_spare_capacity_set_len() {
+    let mut vec = VecSoa3::<u16, u32, u64>::with_capacity(4);
+
+    vec.push((1, 2, 3));
+
+    let (a, b, c) = vec.spare_capacity_mut();
+    assert_eq!(a.len(), 3);
+    assert_eq!(b.len(), 3);
+    assert_eq!(c.len(), 3);
+
+    for i in 0..3 {
+        a[i].write(4 + i as u16);
+        b[i].write(5 + i as u32);
+        c[i].write(6 + i as u64);
+    }
+
+    unsafe { vec.set_len(4) };
+
+    assert_eq!(vec.len(), 4);
+    assert!(vec.spare_capacity_mut().0.is_empty());
+
+    let (a, b, c) = vec.soa_slices();
+    assert_eq!(a, &[1, 4, 5, 6]);
+    assert_eq!(b, &[2, 5, 6, 7]);
+    assert_eq!(c, &[3, 6, 7, 8]);
+}
+
+#[test]
+pub fn test_try_reserve() {
+    let mut vec = VecSoa3::<u16, u32, u64>::try_with_capacity(2).unwrap();
+    assert_eq!(vec.capacity(), 2);
+
+    vec.try_push((1, 2, 3)).unwrap();
+    vec.try_push((4, 5, 6)).unwrap();
+    vec.try_push((7, 8, 9)).unwrap();
+    assert!(vec.capacity() >= 3);
+
+    vec.try_reserve_exact(10).unwrap();
+    assert_eq!(vec.capacity(), 13);
+
+    let (a, b, c) = vec.soa_slices();
+    assert_eq!(a, &[1, 4, 7]);
+    assert_eq!(b, &[2, 5, 8]);
+    assert_eq!(c, &[3, 6, 9]);
+}
+
+#[test]
+pub fn test_try_reserve_overflow() {
+    let mut vec = VecSoa3::<u16, u32, u64>::new();
+    vec.push((1, 2, 3));
+
+    assert_eq!(
+        vec.try_reserve(usize::MAX),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(
+        vec.try_reserve(isize::MAX as usize),
+        Err(TryReserveError::CapacityOverflow)
+    );
+
+    assert_eq!(vec.len(), 1);
+    assert_eq!(vec.index(0), (&1, &2, &3));
+}
+
+#[test]
+pub fn test_try_reserve_alloc_error() {
+    let mut vec = VecSoa3::<u8, u16, u64>::new();
+    vec.push((1, 2, 3));
+
+    let capacity = vec.capacity();
+    let result = vec.try_reserve_exact(isize::MAX as usize / 8);
+    assert!(matches!(result, Err(TryReserveError::AllocError { .. })));
+
+    assert_eq!(vec.len(), 1);
+    assert_eq!(vec.capacity(), capacity);
+    assert_eq!(vec.index(0), (&1, &2, &3));
+
+    vec.push((4, 5, 6));
+    assert_eq!(vec.index(1), (&4, &5, &6));
+}
+
+#[test]
+pub fn test_try_with_capacity_massive() {
+    let result = VecSoa3::<u16, u32, u64>::try_with_capacity(isize::MAX as usize + 1);
+    assert_eq!(result.err(), Some(TryReserveError::CapacityOverflow));
+}
+
+#[test]
+pub fn test_reserve_exact() {
+    let mut vec = VecSoa3::<u16, u32, u64>::new();
+    vec.push((1, 2, 3));
+
+    vec.reserve_exact(7);
+    assert_eq!(vec.capacity(), 8);
+
+    vec.reserve_exact(2);
+    assert_eq!(vec.capacity(), 8);
+}
+
+#[test]
+pub fn test_shrink() {
+    let mut vec = VecSoa3::<u16, u32, u64>::with_capacity(64);
+
+    vec.push((1, 2, 3));
+    vec.push((4, 5, 6));
+
+    vec.shrink_to(16);
+    assert_eq!(vec.capacity(), 16);
+
+    vec.shrink_to(32);
+    assert_eq!(vec.capacity(), 16);
+
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 2);
+
+    let (a, b, c) = vec.soa_slices();
+    assert_eq!(a, &[1, 4]);
+    assert_eq!(b, &[2, 5]);
+    assert_eq!(c, &[3, 6]);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 0);
+
+    vec.push((7, 8, 9));
+    assert_eq!(vec.index(0), (&7, &8, &9));
+}
+
+#[test]
+pub fn test_growth_policy() {
+    let mut vec = VecSoa3::<u16, u32, u64>::with_capacity(4);
+    assert_eq!(vec.growth_policy(), GrowthPolicy::Double);
+
+    for i in 0..5 {
+        vec.push((i, i as u32, i as u64));
+    }
+    assert_eq!(vec.capacity(), 8);
+
+    vec.set_growth_policy(GrowthPolicy::OneAndHalf);
+    vec.reserve(4);
+    assert_eq!(vec.capacity(), 12);
+
+    vec.set_growth_policy(GrowthPolicy::Fixed(3));
+    vec.reserve(8);
+    assert_eq!(vec.capacity(), 15);
+    vec.reserve(20);
+    assert_eq!(vec.capacity(), 25);
+
+    let mut vec =
+        VecSoa3::<u16, u32, u64>::new().with_growth_policy(GrowthPolicy::PageRounded(4096));
+    vec.push((1, 2, 3));
+    assert_eq!(vec.capacity(), 4096 / 8);
+}
+
+#[test]
+pub fn test_validate() {
+    let mut vec = VecSoa3::<u16, u32, u64>::with_capacity(4);
+    vec.validate();
+
+    vec.push((1, 2, 3));
+
+    let (a, b, c) = vec.spare_capacity_mut();
+    a[0].write(4);
+    b[0].write(5);
+    c[0].write(6);
+
+    unsafe { vec.set_len(2) };
+    vec.validate();
+
+    vec.swap_remove(0);
+    vec.validate();
+
+    vec.shrink_to_fit();
+    vec.validate();
+
+    vec.clear();
+    vec.validate();
+}
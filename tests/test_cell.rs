@@ -0,0 +1,77 @@
+#![cfg(feature = "alloc")]
+use vec_soa::prelude::*;
+
+fn cell() -> CellVecSoa3<u32, String, f32> {
+    let mut vec = VecSoa3::new();
+    for i in 0..4 {
+        vec.push((i, i.to_string(), i as f32));
+    }
+    CellVecSoa3::new(vec)
+}
+
+#[test]
+fn test_borrow_columns() {
+    let cell = cell();
+
+    let a = cell.borrow_column::<0>();
+    let a2 = cell.borrow_column::<0>();
+    let mut c = cell.borrow_column_mut::<2>();
+
+    for (c, a) in c.iter_mut().zip(a.iter()) {
+        *c += *a as f32;
+    }
+
+    assert_eq!(&*a2, &[0, 1, 2, 3]);
+    drop(c);
+
+    assert_eq!(&*cell.borrow_column::<2>(), &[0.0, 2.0, 4.0, 6.0]);
+    assert_eq!(cell.borrow_column::<1>()[3], "3");
+    assert_eq!(cell.len(), 4);
+}
+
+#[test]
+fn test_borrow_conflicts() {
+    let cell = cell();
+
+    let a = cell.borrow_column::<0>();
+    assert_eq!(
+        cell.try_borrow_column_mut::<0>().err(),
+        Some(BorrowError::Borrowed { column: 0 })
+    );
+    drop(a);
+
+    let b = cell.borrow_column_mut::<1>();
+    assert_eq!(
+        cell.try_borrow_column::<1>().err(),
+        Some(BorrowError::MutablyBorrowed { column: 1 })
+    );
+    assert_eq!(
+        cell.try_borrow_column_mut::<1>().err(),
+        Some(BorrowError::Borrowed { column: 1 })
+    );
+
+    // Other columns are unaffected.
+    assert!(cell.try_borrow_column_mut::<0>().is_ok());
+    drop(b);
+
+    assert!(cell.try_borrow_column_mut::<1>().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "CellVecSoa3::borrow_column_mut: column 2 is already borrowed")]
+fn test_borrow_mut_panics() {
+    let cell = cell();
+    let _c = cell.borrow_column::<2>();
+    let _ = cell.borrow_column_mut::<2>();
+}
+
+#[test]
+fn test_into_inner() {
+    let mut cell = cell();
+    cell.get_mut().push((4, "4".to_string(), 4.0));
+    cell.borrow_column_mut::<0>()[4] = 40;
+
+    let vec = cell.into_inner();
+    assert_eq!(vec.soa_slices().0, &[0, 1, 2, 3, 40]);
+    vec.validate();
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "checked")]
+
+use vec_soa::data::RawDataPtr;
+
+#[test]
+#[should_panic(expected = "RawDataPtr::write: element at index 0 is already initialized")]
+pub fn test_double_write() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe {
+        data.write(0, 1);
+        data.write(0, 2);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::write: range 2..2+1 is out of bounds for capacity 2")]
+pub fn test_write_out_of_bounds() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe { data.write(2, 1) };
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::slice: element at index 1 is not initialized")]
+pub fn test_slice_uninit() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe {
+        data.write(0, 1);
+        data.slice(0, 2);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::offset: range 3..3+0 is out of bounds for capacity 2")]
+pub fn test_offset_out_of_bounds() {
+    let data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe { data.offset(3) };
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::swap_remove: index 1 is out of bounds for len 1")]
+pub fn test_swap_remove_out_of_bounds() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe {
+        data.write(0, 1);
+        data.swap_remove(1, 1);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::drop_to: element at index 0 is not initialized")]
+pub fn test_double_drop() {
+    let mut data = RawDataPtr::<String>::with_capacity(2);
+    unsafe {
+        data.write(0, String::from("a"));
+        data.drop_to(1);
+        data.drop_to(1);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::grow: expected an allocation of 4 elements, but it has 2")]
+pub fn test_grow_wrong_capacity() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe { data.grow(4, 8) };
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::data_at: use of data that was deallocated or reallocated")]
+pub fn test_use_after_grow() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe {
+        data.write(0, 1);
+        let stale = data.shallow_copy();
+        data.grow(2, 4);
+        stale.data_at(0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::slice: use of data that was deallocated or reallocated")]
+pub fn test_use_after_dealloc() {
+    let mut data = RawDataPtr::<u32>::with_capacity(2);
+    unsafe {
+        let stale = data.shallow_copy();
+        data.dealloc(2);
+        stale.slice(0, 0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "RawDataPtr::validate: element at index 1 is initialized past len 1")]
+pub fn test_validate() {
+    let mut data = RawDataPtr::<u32>::with_capacity(4);
+    unsafe {
+        data.write(0, 1);
+        data.write(1, 2);
+    }
+    data.validate(4, 2);
+    data.validate(4, 1);
+}
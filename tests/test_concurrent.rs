@@ -0,0 +1,120 @@
+#![cfg(feature = "alloc")]
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use vec_soa::prelude::*;
+
+#[test]
+fn test_push_and_snapshot() {
+    let vec = ConcurrentVecSoa3::<u32, String, f32>::new();
+    assert!(vec.is_empty());
+
+    for i in 0..100 {
+        assert_eq!(vec.push((i, i.to_string(), i as f32)), i as usize);
+    }
+
+    let snapshot = vec.snapshot();
+    assert_eq!(snapshot.len(), 100);
+    assert_eq!(vec.reserved_len(), 100);
+
+    // Pushing again doesn't affect an existing snapshot.
+    vec.push((100, "100".to_string(), 100.0));
+    assert_eq!(snapshot.len(), 100);
+    assert_eq!(vec.len(), 101);
+
+    assert_eq!(snapshot.get(40), Some((&40, &"40".to_string(), &40.0)));
+    assert_eq!(snapshot.get(100), None);
+
+    // Segments hold 32, 64, 128... rows.
+    let lens: Vec<_> = snapshot.segments().map(|slice| slice.len()).collect();
+    assert_eq!(lens, [32, 64, 4]);
+
+    let mut all = Vec::new();
+    for slice in snapshot.segments() {
+        all.extend_from_slice(slice.soa_slices().0);
+    }
+    assert_eq!(all, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_push_from_threads() {
+    const THREADS: u32 = 8;
+    const ROWS: u32 = 1000;
+
+    let vec = ConcurrentVecSoa2::<u32, u64>::new();
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let vec = &vec;
+            scope.spawn(move || {
+                for i in 0..ROWS {
+                    let index = vec.push((t * ROWS + i, u64::from(t)));
+                    assert!(vec.snapshot().len() <= vec.reserved_len());
+                    assert!(index < vec.reserved_len());
+                }
+            });
+        }
+
+        // Readers only ever see fully written rows.
+        let snapshot = vec.snapshot();
+        for segment in snapshot.segments() {
+            let (a, b) = segment.soa_slices();
+            for (a, b) in a.iter().zip(b) {
+                assert_eq!(u64::from(a / ROWS), *b);
+            }
+        }
+    });
+
+    let snapshot = vec.snapshot();
+    assert_eq!(snapshot.len(), (THREADS * ROWS) as usize);
+
+    let mut all: Vec<u32> = snapshot
+        .segments()
+        .flat_map(|slice| slice.soa_slices().0.to_vec())
+        .collect();
+    all.sort_unstable();
+    assert_eq!(all, (0..THREADS * ROWS).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let rc = Rc::new(());
+    let vec = ConcurrentVecSoa3::<Counted, (), Rc<()>>::new();
+    for _ in 0..500 {
+        vec.push((Counted, (), rc.clone()));
+    }
+    assert_eq!(Rc::strong_count(&rc), 501);
+
+    drop(vec);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 500);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn test_zst_columns() {
+    let vec = ConcurrentVecSoa2::<(), u8>::default();
+    for i in 0..40 {
+        vec.push(((), i));
+    }
+
+    let snapshot = vec.snapshot();
+    assert_eq!(snapshot.get(39), Some((&(), &39)));
+    assert_eq!(
+        snapshot
+            .segments()
+            .map(|slice| slice.soa_slices().0.len())
+            .sum::<usize>(),
+        40
+    );
+}
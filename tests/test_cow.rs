@@ -0,0 +1,208 @@
+#![cfg(feature = "alloc")]
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use vec_soa::prelude::*;
+
+fn cow() -> CowVecSoa3<u32, String, f32> {
+    (0..10u32).map(|i| (i, i.to_string(), i as f32)).collect()
+}
+
+#[test]
+fn test_clone_shares_columns() {
+    let vec = cow();
+    assert_eq!(vec.shared_columns(), 0);
+
+    let mut copy = vec.clone();
+    assert_eq!(vec.shared_columns(), 3);
+    assert!(copy.is_column_shared::<1>());
+
+    // Only the mutated column is copied.
+    copy.column_mut::<2>()[3] = -1.0;
+    assert!(!copy.is_column_shared::<2>());
+    assert!(copy.is_column_shared::<0>());
+    assert_eq!(copy.shared_columns(), 2);
+
+    assert_eq!(vec.soa_slices().2[3], 3.0);
+    assert_eq!(copy.soa_slices().2[3], -1.0);
+    assert_eq!(copy.soa_slices().0, vec.soa_slices().0);
+
+    // A column that isn't shared anymore is mutated in place.
+    drop(vec);
+    assert_eq!(copy.shared_columns(), 0);
+    copy.column_mut::<0>()[0] = 100;
+    assert_eq!(copy.soa_slices().0[0], 100);
+}
+
+#[test]
+fn test_column_mut_unshares_one_column() {
+    let vec = cow();
+    let mut copy = vec.clone();
+
+    for b in copy.column_mut::<1>() {
+        b.push('!');
+    }
+
+    // Reading the other columns never copies them.
+    let sum: f32 = copy.iter().map(|(_, _, c)| *c).sum();
+    assert_eq!(sum, 45.0);
+
+    assert_eq!(copy.shared_columns(), 2);
+    assert!(!copy.is_column_shared::<1>());
+    assert_eq!(vec.soa_slices().1[4], "4");
+    assert_eq!(copy.soa_slices().1[4], "4!");
+
+    // Mutating the column again doesn't copy it a second time.
+    let ptr = copy.soa_slices().1.as_ptr();
+    copy.column_mut::<1>()[0].push('?');
+    assert_eq!(copy.soa_slices().1.as_ptr(), ptr);
+    assert_eq!(copy.soa_slices().1[0], "0!?");
+}
+
+#[test]
+fn test_push_pop_truncate() {
+    let vec = cow();
+    let mut copy = vec.clone();
+
+    copy.push((10, "10".to_string(), 10.0));
+    assert_eq!(copy.len(), 11);
+    assert_eq!(vec.len(), 10);
+    assert_eq!(copy.pop(), Some((10, "10".to_string(), 10.0)));
+
+    let mut short = vec.clone();
+    short.truncate(4);
+    assert_eq!(short.soa_slices().0, &[0, 1, 2, 3]);
+    assert_eq!(vec.len(), 10);
+
+    short.clear();
+    assert!(short.is_empty());
+    assert_eq!(short.pop(), None);
+
+    let mut empty = CowVecSoa2::<u8, u8>::new();
+    empty.push((1, 2));
+    assert_eq!(empty.soa_slices(), (&[1][..], &[2][..]));
+}
+
+#[test]
+fn test_clone_doesnt_clone_rows() {
+    let rc = Rc::new(());
+    let vec: CowVecSoa2<Rc<()>, u8> = (0..5).map(|i| (rc.clone(), i)).collect();
+    assert_eq!(Rc::strong_count(&rc), 6);
+
+    let mut copy = vec.clone();
+    assert_eq!(Rc::strong_count(&rc), 6);
+
+    copy.column_mut::<1>()[0] = 9;
+    assert_eq!(Rc::strong_count(&rc), 6);
+
+    copy.column_mut::<0>();
+    assert_eq!(Rc::strong_count(&rc), 11);
+
+    drop(vec);
+    drop(copy);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+/// A value that panics when cloned, to copy a shared column halfway through.
+#[derive(Debug, PartialEq)]
+struct PanicOnClone(u32);
+
+impl Clone for PanicOnClone {
+    fn clone(&self) -> Self {
+        panic!("PanicOnClone");
+    }
+}
+
+/// Builds a vector of four rows whose second column is shared with the returned clone,
+/// while the first column isn't shared.
+fn shared_panicky() -> (CowVecSoa2<u32, PanicOnClone>, CowVecSoa2<u32, PanicOnClone>) {
+    let mut vec: CowVecSoa2<u32, PanicOnClone> = (0..4).map(|i| (i, PanicOnClone(i))).collect();
+    let copy = vec.clone();
+    vec.column_mut::<0>();
+    assert_eq!(vec.shared_columns(), 1);
+    (vec, copy)
+}
+
+/// Checks that the vector still holds its four rows after a failed operation.
+fn assert_intact(vec: &CowVecSoa2<u32, PanicOnClone>) {
+    assert_eq!(vec.len(), 4);
+    let (a, b) = vec.soa_slices();
+    assert_eq!(a, &[0, 1, 2, 3]);
+    assert_eq!(b.iter().map(|x| x.0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+}
+
+#[test]
+fn test_panicking_clone_in_push() {
+    let (mut vec, copy) = shared_panicky();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| vec.push((4, PanicOnClone(4)))));
+    assert!(result.is_err());
+    assert_intact(&vec);
+
+    drop(copy);
+    vec.push((4, PanicOnClone(4)));
+    assert_eq!(vec.soa_slices().0, &[0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_panicking_clone_in_pop() {
+    let (mut vec, copy) = shared_panicky();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| vec.pop()));
+    assert!(result.is_err());
+    assert_intact(&vec);
+
+    drop(copy);
+    assert_eq!(vec.pop(), Some((3, PanicOnClone(3))));
+    assert_eq!(vec.len(), 3);
+}
+
+#[test]
+fn test_panicking_clone_in_truncate() {
+    let (mut vec, copy) = shared_panicky();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| vec.truncate(1)));
+    assert!(result.is_err());
+    assert_intact(&vec);
+    assert_eq!(copy.len(), 4);
+
+    // The first column, which isn't shared, must not have been truncated either.
+    drop(copy);
+    vec.push((4, PanicOnClone(4)));
+    assert_eq!(vec.soa_slices().0, &[0, 1, 2, 3, 4]);
+    vec.truncate(1);
+    assert_eq!(vec.soa_slices().0, &[0]);
+}
+
+#[test]
+fn test_panicking_clone_in_column_mut() {
+    let (mut vec, copy) = shared_panicky();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        vec.column_mut::<1>();
+    }));
+    assert!(result.is_err());
+    assert!(vec.is_column_shared::<1>());
+    assert_intact(&vec);
+
+    drop(copy);
+    vec.column_mut::<1>()[0] = PanicOnClone(10);
+    assert_eq!(vec.soa_slices().1[0], PanicOnClone(10));
+}
+
+#[test]
+fn test_panicking_clone_keeps_earlier_copies() {
+    // The first column is copied before the second one panics, so its data pointer
+    // has to follow the copy. The original buffer is released along with the clone.
+    let mut vec: CowVecSoa2<u32, PanicOnClone> = (0..4).map(|i| (i, PanicOnClone(i))).collect();
+    let copy = vec.clone();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        vec.column_mut::<0>()[0] = 0;
+        vec.column_mut::<1>();
+    }));
+    assert!(result.is_err());
+    assert!(!vec.is_column_shared::<0>());
+    drop(copy);
+    assert_intact(&vec);
+}
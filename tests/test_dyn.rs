@@ -0,0 +1,183 @@
+#![cfg(feature = "alloc")]
+use std::any::TypeId;
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vec_soa::prelude::*;
+
+/// Pushes a row of a `u32` and a `String` onto a table with exactly those columns.
+fn push(table: &mut DynVecSoa, a: u32, b: &str) -> usize {
+    let mut a = ManuallyDrop::new(a);
+    let mut b = ManuallyDrop::new(b.to_string());
+    let row = [NonNull::from(&mut *a).cast(), NonNull::from(&mut *b).cast()];
+
+    // SAFETY: The pointers match the column types, and the values are never dropped.
+    unsafe { table.push_row(&row) }
+}
+
+#[test]
+fn test_push_and_column() {
+    let mut table = DynVecSoa::new([ColumnDesc::of::<u32>(), ColumnDesc::of::<String>()]);
+    assert_eq!(table.columns().len(), 2);
+    assert_eq!(table.column_index(TypeId::of::<String>()), Some(1));
+
+    for i in 0..10 {
+        assert_eq!(push(&mut table, i, &i.to_string()), i as usize);
+    }
+
+    assert_eq!(table.len(), 10);
+    assert!(table.capacity() >= 10);
+    assert_eq!(
+        table.column::<u32>().unwrap(),
+        &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+    );
+    assert_eq!(table.column::<String>().unwrap()[7], "7");
+    assert!(table.column::<u64>().is_none());
+
+    table.column_mut::<u32>().unwrap()[3] = 30;
+    assert_eq!(table.column::<u32>().unwrap()[3], 30);
+}
+
+#[test]
+fn test_swap_remove() {
+    let mut table = DynVecSoa::new([ColumnDesc::of::<u32>(), ColumnDesc::of::<String>()]);
+    for i in 0..5 {
+        push(&mut table, i, &i.to_string());
+    }
+
+    table.swap_remove(1);
+    table.swap_remove(3);
+    table.swap_remove(0);
+
+    assert_eq!(table.column::<u32>().unwrap(), &[2, 4]);
+    assert_eq!(table.column::<String>().unwrap(), &["2", "4"]);
+
+    table.clear();
+    assert!(table.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "duplicate column")]
+fn test_duplicate_column() {
+    DynVecSoa::new([ColumnDesc::of::<u32>(), ColumnDesc::of::<u32>()]);
+}
+
+#[test]
+fn test_move_row_to() {
+    let mut source = DynVecSoa::new([
+        ColumnDesc::of::<u32>(),
+        ColumnDesc::of::<String>(),
+        ColumnDesc::of::<Vec<u8>>(),
+    ]);
+
+    for i in 0..3u32 {
+        let mut a = ManuallyDrop::new(i);
+        let mut b = ManuallyDrop::new(i.to_string());
+        let mut c = ManuallyDrop::new(vec![i as u8; 4]);
+        let row = [
+            NonNull::from(&mut *a).cast(),
+            NonNull::from(&mut *b).cast(),
+            NonNull::from(&mut *c).cast(),
+        ];
+        unsafe { source.push_row(&row) };
+    }
+
+    // Removing a component: the Vec<u8> column is dropped.
+    let mut narrow = DynVecSoa::new([ColumnDesc::of::<String>(), ColumnDesc::of::<u32>()]);
+    assert_eq!(source.move_row_to(0, &mut narrow), 0);
+    assert_eq!(source.len(), 2);
+    assert_eq!(source.column::<u32>().unwrap(), &[2, 1]);
+    assert_eq!(narrow.column::<u32>().unwrap(), &[0]);
+    assert_eq!(narrow.column::<String>().unwrap(), &["0"]);
+
+    // Adding a component: the f64 column is initialized by the caller.
+    let mut wide = DynVecSoa::new([
+        ColumnDesc::of::<f64>(),
+        ColumnDesc::of::<u32>(),
+        ColumnDesc::of::<String>(),
+    ]);
+    let index = unsafe {
+        narrow.move_row_to_with(0, &mut wide, |column, ptr| {
+            assert_eq!(column, 0);
+            ptr.cast::<f64>().write(1.5);
+        })
+    };
+
+    assert_eq!(index, 0);
+    assert!(narrow.is_empty());
+    assert_eq!(wide.column::<f64>().unwrap(), &[1.5]);
+    assert_eq!(wide.column::<String>().unwrap(), &["0"]);
+}
+
+#[test]
+#[should_panic(expected = "missing from the source")]
+fn test_move_row_to_missing_column() {
+    let mut source = DynVecSoa::new([ColumnDesc::of::<u32>(), ColumnDesc::of::<String>()]);
+    push(&mut source, 0, "0");
+
+    let mut other = DynVecSoa::new([ColumnDesc::of::<u64>()]);
+    source.move_row_to(0, &mut other);
+}
+
+#[test]
+fn test_try_clone() {
+    let mut table = DynVecSoa::new([
+        ColumnDesc::of_clone::<u32>(),
+        ColumnDesc::of_clone::<String>(),
+    ]);
+    for i in 0..4 {
+        push(&mut table, i, &i.to_string());
+    }
+
+    let clone = table.try_clone().unwrap();
+    assert_eq!(clone.column::<u32>(), table.column::<u32>());
+    assert_eq!(clone.column::<String>(), table.column::<String>());
+
+    let table = DynVecSoa::new([ColumnDesc::of::<u32>()]);
+    assert!(table.try_clone().is_none());
+}
+
+#[test]
+fn test_zst_column() {
+    let mut table = DynVecSoa::new([ColumnDesc::of::<()>(), ColumnDesc::of::<u16>()]);
+
+    for i in 0..100u16 {
+        let mut value = i;
+        let row = [
+            NonNull::<()>::dangling().cast(),
+            NonNull::from(&mut value).cast(),
+        ];
+        unsafe { table.push_row(&row) };
+    }
+
+    assert_eq!(table.column::<()>().unwrap().len(), 100);
+    assert_eq!(table.column::<u16>().unwrap()[99], 99);
+}
+
+#[test]
+fn test_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let mut table = DynVecSoa::new([ColumnDesc::of::<Counted>(), ColumnDesc::of::<u8>()]);
+    for i in 0..6u8 {
+        let mut a = ManuallyDrop::new(Counted);
+        let mut b = i;
+        let row = [NonNull::from(&mut *a).cast(), NonNull::from(&mut b).cast()];
+        unsafe { table.push_row(&row) };
+    }
+
+    table.swap_remove(2);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+    drop(table);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 6);
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "alloc")]
+use vec_soa::prelude::*;
+
+fn registry() -> Vec<Box<dyn AnySoa>> {
+    let mut a = VecSoa2::<u32, String>::new();
+    let mut b = VecSoa3::<u8, u16, u64>::new();
+    let mut c = VecSoa4::<(), u8, u8, Vec<u8>>::new();
+
+    for i in 0..8 {
+        a.push((i, i.to_string()));
+        b.push((i as u8, i as u16, i as u64));
+        c.push(((), i as u8, i as u8, vec![i as u8]));
+    }
+
+    vec![Box::new(a), Box::new(b), Box::new(c)]
+}
+
+#[test]
+fn test_registry() {
+    let mut tables = registry();
+
+    for table in &mut tables {
+        assert_eq!(table.len(), 8);
+        assert!(table.capacity() >= 8);
+        assert!(table.heap_bytes() > 0);
+
+        table.swap_remove_drop(0);
+        assert_eq!(table.len(), 7);
+
+        table.truncate(10);
+        assert_eq!(table.len(), 7);
+        table.truncate(3);
+        assert_eq!(table.len(), 3);
+
+        table.shrink_to_fit();
+        assert_eq!(table.capacity(), 3);
+
+        table.reserve(5);
+        assert!(table.capacity() >= 8);
+
+        table.clear();
+        assert!(table.is_empty());
+    }
+}
+
+#[test]
+fn test_downcast() {
+    let mut tables = registry();
+    tables[0].swap_remove_drop(1);
+
+    let a = tables[0].downcast_ref::<VecSoa2<u32, String>>().unwrap();
+    assert_eq!(a.soa_slices().0, &[0, 7, 2, 3, 4, 5, 6]);
+    assert!(tables[0].downcast_ref::<VecSoa2<u32, u32>>().is_none());
+
+    let b = tables[1].downcast_mut::<VecSoa3<u8, u16, u64>>().unwrap();
+    b.push((9, 9, 9));
+    assert_eq!(tables[1].len(), 9);
+}
+
+#[test]
+fn test_truncate() {
+    let mut vec = VecSoa2::<u32, String>::new();
+    for i in 0..5 {
+        vec.push((i, i.to_string()));
+    }
+
+    let capacity = vec.capacity();
+    vec.truncate(2);
+    assert_eq!(vec.soa_slices().1, &["0", "1"]);
+    assert_eq!(vec.capacity(), capacity);
+    vec.validate();
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "alloc")]
+use std::mem::MaybeUninit;
+
+use vec_soa::prelude::*;
+
+fn is_aligned<T>(slice: &[T], align: usize) -> bool {
+    (slice.as_ptr() as usize) % align == 0
+}
+
+#[test]
+fn test_aligned_columns() {
+    let mut vec = VecSoa3::<u8, u16, u64>::new().with_column_layout(ColumnLayout::simd(64));
+
+    for i in 0..100 {
+        vec.push((i as u8, i as u16, i as u64));
+
+        let (a, b, c) = vec.soa_slices();
+        assert!(is_aligned(a, 64));
+        assert!(is_aligned(b, 64));
+        assert!(is_aligned(c, 64));
+        assert!(vec.capacity() % 64 == 0);
+    }
+
+    vec.validate();
+}
+
+#[test]
+fn test_padded_capacity() {
+    let layout = ColumnLayout::new(32, 8);
+    let mut vec = VecSoa2::<u32, f32>::with_capacity(5).with_column_layout(layout);
+    assert_eq!(vec.column_layout(), layout);
+    assert_eq!(vec.capacity(), 8);
+
+    vec.reserve_exact(9);
+    assert_eq!(vec.capacity(), 16);
+
+    vec.try_reserve(20).unwrap();
+    assert!(vec.capacity() % 8 == 0);
+
+    vec.push((1, 1.0));
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 8);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn test_relayout_preserves_data() {
+    let mut vec = VecSoa2::<u64, String>::new();
+    for i in 0..10 {
+        vec.push((i, i.to_string()));
+    }
+
+    let vec = vec.with_column_layout(ColumnLayout::new(128, 4));
+    assert!(vec.capacity() % 4 == 0);
+
+    let (a, b) = vec.soa_slices();
+    assert!(is_aligned(a, 128));
+    assert!(is_aligned(b, 128));
+
+    for i in 0..10 {
+        assert_eq!(a[i], i as u64);
+        assert_eq!(b[i], i.to_string());
+    }
+
+    vec.validate();
+}
+
+#[test]
+fn test_padded_tail() {
+    let mut vec = VecSoa2::<f32, u8>::new().with_column_layout(ColumnLayout::new(16, 4));
+    assert_eq!(vec.padded_len(), 0);
+
+    for i in 0..5 {
+        vec.push((i as f32, i));
+    }
+
+    assert_eq!(vec.padded_len(), 8);
+
+    let (a, b) = vec.padded_tail_mut();
+    assert_eq!(a.len(), 3);
+    assert_eq!(b.len(), 3);
+    a.fill(MaybeUninit::new(0.0));
+    b.fill(MaybeUninit::new(0));
+
+    vec.push((5.0, 5));
+    vec.push((6.0, 6));
+    vec.push((7.0, 7));
+    assert_eq!(vec.padded_len(), 8);
+
+    let (a, b) = vec.padded_tail_mut();
+    assert!(a.is_empty());
+    assert!(b.is_empty());
+
+    vec.validate();
+}
+
+#[test]
+fn test_natural_layout() {
+    let mut vec = VecSoa2::<u8, u32>::new();
+    assert_eq!(vec.column_layout(), ColumnLayout::NATURAL);
+
+    vec.push((1, 1));
+    assert_eq!(vec.padded_len(), vec.len());
+    assert!(vec.padded_tail_mut().0.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_align() {
+    ColumnLayout::new(24, 1);
+}
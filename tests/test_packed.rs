@@ -0,0 +1,204 @@
+#![cfg(feature = "alloc")]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+use vec_soa::prelude::*;
+use vec_soa::{AllocError, Allocator, Global};
+
+#[derive(Default)]
+struct CountingAlloc {
+    allocs: Cell<usize>,
+    live: Cell<usize>,
+}
+
+unsafe impl Allocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        self.live.set(self.live.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+pub fn test_basic() {
+    let mut vec = PackedVecSoa3::<u8, u64, u16>::new();
+
+    assert!(vec.is_empty());
+    assert_eq!(vec.len(), 0);
+
+    for i in 0..100 {
+        vec.push((i as u8, i as u64 * 1000, i as u16 * 10));
+    }
+
+    assert_eq!(vec.len(), 100);
+    assert!(vec.capacity() >= 100);
+    vec.validate();
+
+    for (i, (a, b, c)) in vec.iter().enumerate() {
+        assert_eq!(*a, i as u8);
+        assert_eq!(*b, i as u64 * 1000);
+        assert_eq!(*c, i as u16 * 10);
+    }
+
+    let (_, b, c) = vec.soa_slices();
+    assert_eq!(b.as_ptr() as usize % 8, 0);
+    assert_eq!(c.as_ptr() as usize % 2, 0);
+
+    assert_eq!(vec.swap_remove(0), (0, 0, 0));
+    assert_eq!(vec.index(0), (&99, &99000, &990));
+
+    vec.clear();
+    assert!(vec.is_empty());
+}
+
+#[test]
+pub fn test_single_allocation() {
+    let alloc = CountingAlloc::default();
+
+    {
+        let mut vec = PackedVecSoa8::<u8, u16, u32, u64, u8, u16, u32, u64, _>::new_in(&alloc);
+
+        vec.push((1, 2, 3, 4, 5, 6, 7, 8));
+        assert_eq!(alloc.allocs.get(), 1);
+        assert_eq!(alloc.live.get(), 1);
+
+        vec.reserve_exact(15);
+        assert_eq!(alloc.allocs.get(), 2);
+        assert_eq!(alloc.live.get(), 1);
+
+        vec.push((9, 10, 11, 12, 13, 14, 15, 16));
+        vec.shrink_to_fit();
+        assert_eq!(vec.capacity(), 2);
+        assert_eq!(alloc.live.get(), 1);
+
+        assert_eq!(vec.index(0), (&1, &2, &3, &4, &5, &6, &7, &8));
+        assert_eq!(vec.index(1), (&9, &10, &11, &12, &13, &14, &15, &16));
+        vec.validate();
+    }
+
+    assert_eq!(alloc.live.get(), 0);
+}
+
+#[test]
+pub fn test_zst() {
+    let mut vec = PackedVecSoa3::<u16, (), u32>::new();
+
+    vec.push((1, (), 2));
+    vec.push((3, (), 4));
+    assert_eq!(vec.index(1), (&3, &(), &4));
+
+    let mut vec = PackedVecSoa2::<(), ()>::with_capacity(4);
+    vec.push(((), ()));
+    assert_eq!(vec.len(), 1);
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 1);
+}
+
+#[test]
+pub fn test_drop() {
+    let mut vec = PackedVecSoa2::<String, Vec<u32>>::new();
+
+    for i in 0..10 {
+        vec.push((i.to_string(), vec![i; 4]));
+    }
+
+    let (a, b) = vec.swap_remove(3);
+    assert_eq!(a, "3");
+    assert_eq!(b, vec![3; 4]);
+    assert_eq!(vec.index(3).0, "9");
+}
+
+#[test]
+pub fn test_try_reserve() {
+    let mut vec = PackedVecSoa3::<u8, u16, u64>::new();
+    vec.push((1, 2, 3));
+
+    assert_eq!(
+        vec.try_reserve(usize::MAX),
+        Err(TryReserveError::CapacityOverflow)
+    );
+
+    let result = vec.try_reserve_exact(isize::MAX as usize / 16);
+    assert!(matches!(result, Err(TryReserveError::AllocError { .. })));
+    assert_eq!(vec.index(0), (&1, &2, &3));
+}
+
+#[test]
+pub fn test_column_layout() {
+    let mut vec = PackedVecSoa3::<u8, u32, u16>::new().with_column_layout(ColumnLayout::simd(32));
+
+    for i in 0..5 {
+        vec.push((i as u8, i as u32, i as u16));
+    }
+
+    assert_eq!(vec.capacity() % 32, 0);
+    assert_eq!(vec.padded_len(), 32);
+
+    let (a, b, c) = vec.soa_slices();
+    assert_eq!(a.as_ptr() as usize % 32, 0);
+    assert_eq!(b.as_ptr() as usize % 32, 0);
+    assert_eq!(c.as_ptr() as usize % 32, 0);
+    assert_eq!(b, &[0, 1, 2, 3, 4]);
+    vec.validate();
+}
+
+#[test]
+pub fn test_truncate_and_resize_zeroed() {
+    let mut vec = PackedVecSoa2::<u32, u64>::new();
+
+    for i in 0..8 {
+        vec.push((i, i as u64));
+    }
+
+    vec.truncate(3);
+    vec.resize_zeroed(40);
+    assert_eq!(vec.len(), 40);
+    assert_eq!(&vec.soa_slices().0[..4], &[0, 1, 2, 0]);
+    assert!(vec.soa_slices().1[3..].iter().all(|&x| x == 0));
+    vec.validate();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn test_lock_capacity() {
+    let alloc = CountingAlloc::default();
+    let mut vec = PackedVecSoa2::<u8, u64, _>::with_capacity_in(2, &alloc);
+    vec.lock_capacity();
+
+    vec.push((1, 1));
+    vec.push((2, 2));
+    assert_eq!(vec.try_push((3, 3)), Err(TryReserveError::CapacityLocked));
+    assert_eq!(alloc.allocs.get(), 1);
+
+    vec.unlock_capacity();
+    vec.push((3, 3));
+    assert_eq!(vec.len(), 3);
+}
+
+#[test]
+#[cfg(feature = "stats")]
+pub fn test_alloc_stats() {
+    let mut vec = PackedVecSoa3::<u8, u32, ()>::with_capacity(10);
+    assert_eq!(vec.alloc_stats().allocs, 1);
+
+    for i in 0..10 {
+        vec.push((i, i as u32, ()));
+    }
+
+    vec.reserve_exact(10);
+    let stats = vec.alloc_stats();
+    assert_eq!(stats.reallocs, 1);
+    assert_eq!(stats.bytes_moved, 10 * 5);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert_eq!(vec.alloc_stats().deallocs, 1);
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "alloc")]
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use vec_soa::prelude::*;
+
+struct PanicOnDrop {
+    panics: bool,
+    drops: Rc<Cell<usize>>,
+}
+
+impl PanicOnDrop {
+    fn new(panics: bool, drops: &Rc<Cell<usize>>) -> Self {
+        Self {
+            panics,
+            drops: drops.clone(),
+        }
+    }
+}
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.panics {
+            panic!("PanicOnDrop");
+        }
+    }
+}
+
+type PanicVec = VecSoa3<PanicOnDrop, PanicOnDrop, PanicOnDrop>;
+
+/// Builds a vector of `rows` rows where only the element at (`row`, `column`) panics.
+fn build(drops: &Rc<Cell<usize>>, rows: usize, row: usize, column: usize) -> PanicVec {
+    let mut vec = PanicVec::new();
+
+    for i in 0..rows {
+        vec.push((
+            PanicOnDrop::new((i == row) && (column == 0), drops),
+            PanicOnDrop::new((i == row) && (column == 1), drops),
+            PanicOnDrop::new((i == row) && (column == 2), drops),
+        ));
+    }
+
+    vec
+}
+
+#[test]
+pub fn test_clear_panic() {
+    for column in 0..3 {
+        for row in 0..4 {
+            let drops = Rc::new(Cell::new(0));
+            let mut vec = build(&drops, 4, row, column);
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| vec.clear()));
+            assert!(result.is_err());
+            assert!(vec.is_empty());
+            assert_eq!(drops.get(), 4 * 3);
+
+            vec.push((
+                PanicOnDrop::new(false, &drops),
+                PanicOnDrop::new(false, &drops),
+                PanicOnDrop::new(false, &drops),
+            ));
+
+            drop(vec);
+            assert_eq!(drops.get(), 5 * 3);
+        }
+    }
+}
+
+#[test]
+pub fn test_drop_panic() {
+    for column in 0..3 {
+        for row in 0..4 {
+            let drops = Rc::new(Cell::new(0));
+            let vec = build(&drops, 4, row, column);
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| drop(vec)));
+            assert!(result.is_err());
+            assert_eq!(drops.get(), 4 * 3);
+        }
+    }
+}
+
+#[test]
+pub fn test_swap_remove_panic() {
+    for column in 0..3 {
+        let drops = Rc::new(Cell::new(0));
+        let mut vec = build(&drops, 4, 1, column);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| drop(vec.swap_remove(1))));
+        assert!(result.is_err());
+        assert_eq!(vec.len(), 3);
+        assert_eq!(drops.get(), 3);
+
+        drop(vec);
+        assert_eq!(drops.get(), 4 * 3);
+    }
+}
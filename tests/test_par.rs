@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vec_soa::prelude::*;
+
+fn table(len: u32) -> VecSoa3<u32, u64, String> {
+    let mut vec = VecSoa3::new();
+    for i in 0..len {
+        vec.push((i, 0, String::new()));
+    }
+    vec
+}
+
+#[test]
+fn test_par_for_each_chunk() {
+    let mut vec = table(1000);
+    let chunks = AtomicUsize::new(0);
+
+    vec.par_for_each_chunk(4, 64, |mut chunk| {
+        assert!(chunk.len() <= 64);
+        chunks.fetch_add(1, Ordering::Relaxed);
+
+        let (a, b, c) = chunk.soa_mut_slices();
+        for ((a, b), c) in a.iter().zip(b.iter_mut()).zip(c.iter_mut()) {
+            *b = *a as u64 * 2;
+            *c = a.to_string();
+        }
+    });
+
+    assert_eq!(chunks.load(Ordering::Relaxed), 1000_usize.div_ceil(64));
+
+    let (a, b, c) = vec.soa_slices();
+    for i in 0..1000 {
+        assert_eq!(b[i], a[i] as u64 * 2);
+        assert_eq!(c[i], a[i].to_string());
+    }
+
+    vec.validate();
+}
+
+#[test]
+fn test_par_for_each_chunk_subslice() {
+    let mut vec = table(100);
+    let mut slice = vec.as_mut_slice();
+    let mut tail = slice.index_mut(50..);
+
+    tail.par_for_each_chunk(0, 7, |mut chunk| {
+        for b in chunk.soa_mut_slices().1 {
+            *b = 1;
+        }
+    });
+
+    let b = vec.soa_slices().1;
+    assert!(b[..50].iter().all(|&b| b == 0));
+    assert!(b[50..].iter().all(|&b| b == 1));
+}
+
+#[test]
+fn test_par_empty() {
+    let mut vec = table(0);
+    vec.par_for_each_chunk(4, 8, |_| panic!("no chunks expected"));
+
+    let sum = vec.par_map_reduce(4, 8, |_| 1, || 0, |a, b| a + b);
+    assert_eq!(sum, 0);
+}
+
+#[test]
+fn test_par_map_reduce() {
+    let vec = table(10_000);
+
+    let sum = vec.par_map_reduce(
+        8,
+        100,
+        |chunk| chunk.soa_slices().0.iter().map(|&a| a as u64).sum::<u64>(),
+        || 0,
+        |a, b| a + b,
+    );
+    assert_eq!(sum, (0..10_000u64).sum());
+
+    // Non-commutative reductions still see the chunks in order.
+    let order = vec.par_map_reduce(
+        3,
+        1000,
+        |chunk| vec![chunk.soa_slices().0[0]],
+        Vec::new,
+        |mut a, b| {
+            a.extend(b);
+            a
+        },
+    );
+    assert_eq!(order, (0..10).map(|i| i * 1000).collect::<Vec<_>>());
+}
+
+#[test]
+#[should_panic(expected = "chunk length must be nonzero")]
+fn test_par_zero_chunk_len() {
+    let mut vec = table(10);
+    vec.par_for_each_chunk(2, 0, |_| {});
+}
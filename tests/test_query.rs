@@ -0,0 +1,139 @@
+#![cfg(feature = "alloc")]
+use vec_soa::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position(f32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Velocity(f32);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Name(String);
+
+fn world() -> VecSoa3<Name, Position, Velocity> {
+    let mut vec = VecSoa3::new();
+    for i in 0..4 {
+        vec.push((Name(i.to_string()), Position(i as f32), Velocity(1.0)));
+    }
+    vec
+}
+
+#[test]
+fn test_column() {
+    let mut vec = world();
+
+    let positions = vec.column::<Position>().unwrap();
+    assert_eq!(positions[3], Position(3.0));
+
+    vec.column_mut::<Velocity>().unwrap()[0] = Velocity(-1.0);
+    assert_eq!(vec.soa_slices().2[0], Velocity(-1.0));
+
+    assert_eq!(
+        vec.column::<u32>(),
+        Err(ColumnError::Missing { type_name: "u32" })
+    );
+}
+
+#[test]
+fn test_column_ambiguous() {
+    let mut vec = VecSoa3::<u32, u32, u8>::new();
+    vec.push((1, 2, 3));
+
+    assert!(matches!(
+        vec.column::<u32>(),
+        Err(ColumnError::Ambiguous { .. })
+    ));
+    assert_eq!(vec.column::<u8>().unwrap(), &[3]);
+}
+
+#[test]
+fn test_query() {
+    let mut vec = world();
+
+    for (position, velocity) in vec.query::<(&mut Position, &Velocity)>().unwrap() {
+        position.0 += velocity.0;
+    }
+
+    assert_eq!(
+        vec.column::<Position>().unwrap(),
+        &[Position(1.0), Position(2.0), Position(3.0), Position(4.0)]
+    );
+
+    let names: Vec<&Name> = vec.query::<(&Name,)>().unwrap().map(|(n,)| n).collect();
+    assert_eq!(names[2], &Name("2".to_string()));
+}
+
+#[test]
+fn test_query_iter() {
+    let mut vec = world();
+    let mut query = vec.query::<(&Position, &Name)>().unwrap();
+
+    assert_eq!(query.len(), 4);
+    assert_eq!(query.next_back().unwrap().0, &Position(3.0));
+    assert_eq!(query.next().unwrap().0, &Position(0.0));
+    assert_eq!(query.len(), 2);
+    assert_eq!(query.count(), 2);
+}
+
+#[test]
+fn test_query_ref() {
+    let vec = world();
+    let shared = &vec;
+
+    // Read-only queries only need a shared reference, so several can run at once.
+    let positions = shared.query_ref::<(&Position,)>().unwrap();
+    let names = shared.query_ref::<(&Name, &Velocity)>().unwrap();
+
+    for ((position,), (name, velocity)) in positions.zip(names) {
+        assert_eq!(name.0, position.0.to_string());
+        assert_eq!(velocity, &Velocity(1.0));
+    }
+
+    assert!(matches!(
+        shared.query_ref::<(&Position, &u8)>(),
+        Err(ColumnError::Missing { .. })
+    ));
+}
+
+#[test]
+fn test_query_errors() {
+    let mut vec = world();
+
+    assert!(matches!(
+        vec.query::<(&Position, &mut Position)>(),
+        Err(ColumnError::Aliased { .. })
+    ));
+    assert!(matches!(
+        vec.query::<(&mut Velocity, &mut Velocity)>(),
+        Err(ColumnError::Aliased { .. })
+    ));
+    assert!(matches!(
+        vec.query::<(&Position, &u8)>(),
+        Err(ColumnError::Missing { .. })
+    ));
+
+    // Reading the same column twice is fine.
+    assert_eq!(vec.query::<(&Position, &Position)>().unwrap().count(), 4);
+}
+
+#[test]
+fn test_query_subslice() {
+    let mut vec = world();
+    let mut slice = vec.as_mut_slice();
+    let mut tail = slice.index_mut(2..);
+
+    let velocities: Vec<f32> = tail
+        .query::<(&mut Velocity,)>()
+        .unwrap()
+        .map(|(v,)| {
+            v.0 = 2.0;
+            v.0
+        })
+        .collect();
+
+    assert_eq!(velocities, vec![2.0, 2.0]);
+    assert_eq!(
+        vec.column::<Velocity>().unwrap(),
+        &[Velocity(1.0), Velocity(1.0), Velocity(2.0), Velocity(2.0)]
+    );
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "alloc")]
+use vec_soa::prelude::*;
+
+#[test]
+fn test_push_within_capacity() {
+    let mut vec = VecSoa2::<u32, String>::with_capacity(3);
+
+    for i in 0..3 {
+        assert_eq!(vec.push_within_capacity((i, i.to_string())), Ok(i as usize));
+    }
+
+    let rejected = vec.push_within_capacity((3, "3".to_string()));
+    assert_eq!(rejected, Err((3, "3".to_string())));
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.capacity(), 3);
+
+    vec.validate();
+}
+
+#[test]
+fn test_push_within_zero_capacity() {
+    let mut vec = VecSoa2::<u32, u8>::new();
+    assert_eq!(vec.push_within_capacity((1, 1)), Err((1, 1)));
+    assert_eq!(vec.capacity(), 0);
+}
+
+#[test]
+fn test_insert_within_capacity() {
+    let mut vec = VecSoa2::<u32, String>::with_capacity(5);
+    vec.push((1, "1".to_string()));
+    vec.push((3, "3".to_string()));
+
+    assert!(vec.insert_within_capacity(0, (0, "0".to_string())).is_ok());
+    assert!(vec.insert_within_capacity(2, (2, "2".to_string())).is_ok());
+    assert!(vec.insert_within_capacity(4, (4, "4".to_string())).is_ok());
+
+    let (a, b) = vec.soa_slices();
+    assert_eq!(a, &[0, 1, 2, 3, 4]);
+    assert_eq!(b, &["0", "1", "2", "3", "4"]);
+
+    let rejected = vec.insert_within_capacity(0, (5, "5".to_string()));
+    assert_eq!(rejected, Err((5, "5".to_string())));
+    assert_eq!(vec.len(), 5);
+
+    vec.validate();
+}
+
+#[test]
+#[should_panic]
+fn test_insert_within_capacity_out_of_bounds() {
+    let mut vec = VecSoa2::<u32, u8>::with_capacity(4);
+    vec.push((0, 0));
+    let _ = vec.insert_within_capacity(2, (1, 1));
+}
+
+#[test]
+fn test_lock_capacity() {
+    let mut vec = VecSoa2::<u32, u64>::with_capacity(4);
+    vec.lock_capacity();
+    assert!(vec.is_capacity_locked());
+
+    // Nothing here needs to reallocate.
+    for i in 0..4 {
+        vec.push((i, i as u64));
+    }
+
+    vec.swap_remove(0);
+    vec.reserve(1);
+    vec.shrink_to(4);
+    vec.clear();
+
+    vec.unlock_capacity();
+    assert!(!vec.is_capacity_locked());
+    vec.reserve(10);
+    assert!(vec.capacity() >= 10);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "VecSoa2::reserve: reallocation while the capacity is locked")]
+fn test_lock_capacity_push() {
+    let mut vec = VecSoa2::<u32, u64>::with_capacity(2);
+    vec.lock_capacity();
+
+    for i in 0..3 {
+        vec.push((i, i as u64));
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_lock_capacity_try_reserve() {
+    let mut vec = VecSoa2::<u32, u64>::new();
+    vec.lock_capacity();
+
+    assert_eq!(vec.try_reserve(1), Err(TryReserveError::CapacityLocked));
+    assert_eq!(
+        vec.try_reserve_exact(1),
+        Err(TryReserveError::CapacityLocked)
+    );
+    assert_eq!(vec.try_push((1, 2)), Err(TryReserveError::CapacityLocked));
+    assert_eq!(vec.capacity(), 0);
+
+    vec.unlock_capacity();
+    assert_eq!(vec.try_push((1, 2)), Ok(()));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "VecSoa2::reserve_exact: reallocation while the capacity is locked")]
+fn test_lock_capacity_reserve_exact() {
+    let mut vec = VecSoa2::<u32, u64>::new();
+    vec.lock_capacity();
+    vec.reserve_exact(1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "VecSoa2::shrink_to: reallocation while the capacity is locked")]
+fn test_lock_capacity_shrink() {
+    let mut vec = VecSoa2::<u32, u64>::with_capacity(8);
+    vec.lock_capacity();
+    vec.shrink_to_fit();
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vec_soa::prelude::*;
+
+type Table = VecSoa4<f32, f32, f32, String>;
+type Slice = SliceSoa4<f32, f32, f32, String>;
+
+fn table() -> Table {
+    let mut vec = Table::new();
+    for i in 0..64 {
+        vec.push((i as f32, 1.0, 0.0, i.to_string()));
+    }
+    vec
+}
+
+#[test]
+fn test_batches() {
+    let mut schedule = Schedule::<Slice>::new();
+
+    let a = schedule.add_system::<(Read<0>, Write<2>)>(|_| {});
+    let b = schedule.add_system::<(Read<0>, Read<1>)>(|_| {});
+    let c = schedule.add_system::<(Write<1>,)>(|_| {});
+    let d = schedule.add_system::<(Read<2>, Write<3>)>(|_| {});
+    let e = schedule.add_system::<(Read<3>,)>(|_| {});
+
+    assert_eq!(schedule.len(), 5);
+    assert_eq!(schedule.batch_of(a), 0);
+    assert_eq!(schedule.batch_of(b), 0);
+    // Writes a column that b reads, so it runs after b.
+    assert_eq!(schedule.batch_of(c), 1);
+    // Reads the column that a writes.
+    assert_eq!(schedule.batch_of(d), 1);
+    assert_eq!(schedule.batch_of(e), 2);
+    assert_eq!(schedule.batch_count(), 3);
+}
+
+#[test]
+fn test_run() {
+    let mut vec = table();
+    let runs = AtomicUsize::new(0);
+    let mut schedule = Schedule::<Slice>::new();
+
+    schedule.add_system::<(Read<0>, Read<1>, Write<2>)>(|(x, v, out)| {
+        for ((out, x), v) in out.iter_mut().zip(x).zip(v) {
+            *out = x + v;
+        }
+        runs.fetch_add(1, Ordering::Relaxed);
+    });
+
+    schedule.add_system::<(Write<3>,)>(|(names,)| {
+        for name in names.iter_mut() {
+            name.push('!');
+        }
+        runs.fetch_add(1, Ordering::Relaxed);
+    });
+
+    schedule.add_system::<(Read<2>, Write<1>)>(|(out, v)| {
+        for (v, out) in v.iter_mut().zip(out) {
+            *v = *out * 2.0;
+        }
+        runs.fetch_add(1, Ordering::Relaxed);
+    });
+
+    assert_eq!(schedule.batch_count(), 2);
+
+    schedule.run(&mut vec);
+    schedule.run(&mut vec);
+    drop(schedule);
+
+    assert_eq!(runs.load(Ordering::Relaxed), 6);
+
+    let (x, v, out, names) = vec.soa_slices();
+    assert_eq!(x[10], 10.0);
+    // The second run sees the velocities written by the first.
+    assert_eq!(out[10], 10.0 + 22.0);
+    assert_eq!(v[10], 64.0);
+    assert_eq!(names[10], "10!!");
+}
+
+#[test]
+#[should_panic(expected = "column 1 is written and also accessed by the same system")]
+fn test_aliased_system() {
+    let mut schedule = Schedule::<SliceSoa2<u32, u32>>::new();
+    schedule.add_system::<(Read<1>, Write<1>)>(|_| {});
+}
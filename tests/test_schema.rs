@@ -0,0 +1,95 @@
+#![cfg(feature = "alloc")]
+use std::alloc::Layout;
+use std::any::{Any, TypeId};
+use std::mem;
+
+use vec_soa::prelude::*;
+
+#[test]
+fn test_schema() {
+    type Table = VecSoa3<u8, String, [f32; 3]>;
+
+    assert_eq!(Table::COLUMNS, 3);
+
+    let columns: Vec<ColumnInfo> = Table::columns().collect();
+    assert_eq!(columns.len(), 3);
+
+    assert_eq!(columns[0].index, 0);
+    assert_eq!(columns[0].layout, Layout::new::<u8>());
+    assert_eq!(columns[0].type_id, TypeId::of::<u8>());
+    assert_eq!(columns[0].type_name, "u8");
+
+    assert_eq!(columns[1].type_id, TypeId::of::<String>());
+    assert!(columns[1].type_name.ends_with("String"));
+
+    assert_eq!(columns[2].layout, Layout::new::<[f32; 3]>());
+    assert_eq!(columns[2].type_name, "[f32; 3]");
+
+    assert_eq!(Table::column(3), None);
+    assert_eq!(
+        <SliceSoa3<u8, String, [f32; 3]> as SoaSchema>::column(1),
+        Some(columns[1])
+    );
+}
+
+/// Sums up the bytes used by the rows of each column.
+#[derive(Default)]
+struct ByteCounter {
+    bytes: Vec<usize>,
+}
+
+impl ColumnVisitor for ByteCounter {
+    fn visit<T: 'static>(&mut self, index: usize, column: &[T]) {
+        assert_eq!(index, self.bytes.len());
+        self.bytes.push(mem::size_of_val(column));
+    }
+}
+
+/// Resets every `u32` column to zero, leaving every other column untouched.
+struct ZeroU32;
+
+impl ColumnVisitor for ZeroU32 {
+    fn visit<T: 'static>(&mut self, _: usize, _: &[T]) {}
+
+    fn visit_mut<T: 'static>(&mut self, _: usize, column: &mut [T]) {
+        for value in column {
+            if let Some(value) = (value as &mut dyn Any).downcast_mut::<u32>() {
+                *value = 0;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_for_each_column() {
+    let mut vec = VecSoa3::<u8, u32, u64>::new();
+    for i in 0..5 {
+        vec.push((i, i as u32, i as u64));
+    }
+
+    let mut counter = ByteCounter::default();
+    vec.for_each_column(&mut counter);
+    assert_eq!(counter.bytes, vec![5, 20, 40]);
+
+    let mut counter = ByteCounter::default();
+    vec.as_slice().index(1..3).for_each_column(&mut counter);
+    assert_eq!(counter.bytes, vec![2, 8, 16]);
+
+    let mut counter = ByteCounter::default();
+    vec.for_each_column_mut(&mut counter);
+    assert_eq!(counter.bytes, vec![5, 20, 40]);
+}
+
+#[test]
+fn test_for_each_column_mut() {
+    let mut vec = VecSoa2::<u32, u64>::new();
+    for i in 0..5 {
+        vec.push((i, i as u64));
+    }
+
+    vec.for_each_column_mut(&mut ZeroU32);
+
+    let (a, b) = vec.soa_slices();
+    assert_eq!(a, &[0, 0, 0, 0, 0]);
+    assert_eq!(b, &[0, 1, 2, 3, 4]);
+}
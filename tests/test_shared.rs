@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+use std::thread;
+
+use vec_soa::prelude::*;
+
+fn shared() -> SharedVecSoa3<u32, f32, String> {
+    let mut vec = VecSoa3::new();
+    for i in 0..100 {
+        vec.push((i, 0.0, i.to_string()));
+    }
+    SharedVecSoa3::new(vec)
+}
+
+#[test]
+fn test_read_write_columns() {
+    let shared = shared();
+
+    {
+        let a = shared.read_column::<0>();
+        let a2 = shared.read_column::<0>();
+        assert_eq!(a.len(), 100);
+        assert_eq!(a[10], a2[10]);
+    }
+
+    shared.write_column::<1>()[5] = 1.5;
+    assert_eq!(shared.read_column::<1>()[5], 1.5);
+    assert_eq!(shared.len(), 100);
+}
+
+#[test]
+fn test_lock_columns() {
+    let shared = shared();
+
+    let mut columns = shared.lock_columns::<(Write<1>, Read<0>)>();
+    let (b, a) = &mut *columns;
+    for (b, a) in b.iter_mut().zip(a.iter()) {
+        *b = *a as f32 * 2.0;
+    }
+    drop(columns);
+
+    assert_eq!(shared.read_column::<1>()[50], 100.0);
+}
+
+#[test]
+#[should_panic(expected = "column 2 is locked more than once")]
+fn test_lock_columns_twice() {
+    let shared = shared();
+    let _ = shared.lock_columns::<(Read<2>, Write<2>)>();
+}
+
+#[test]
+fn test_structure() {
+    let shared = shared();
+    assert_eq!(shared.push((100, 0.0, "100".to_string())), 100);
+
+    shared.lock_structure().swap_remove(0);
+    assert_eq!(shared.len(), 100);
+    assert_eq!(shared.read_column::<0>()[0], 100);
+
+    let vec = shared.into_inner();
+    vec.validate();
+}
+
+#[test]
+fn test_threads() {
+    let shared = shared();
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    let mut columns = shared.lock_columns::<(Read<0>, Write<1>)>();
+                    let (a, b) = &mut *columns;
+                    for (a, b) in a.iter().zip(b.iter_mut()) {
+                        *b += *a as f32;
+                    }
+                }
+            });
+
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    let mut columns = shared.lock_columns::<(Write<1>, Read<2>)>();
+                    let (b, c) = &mut *columns;
+                    assert_eq!(b.len(), c.len());
+                }
+            });
+
+            scope.spawn(|| {
+                for i in 0..10 {
+                    shared.push((0, 0.0, i.to_string()));
+                }
+            });
+        }
+    });
+
+    assert_eq!(shared.len(), 140);
+    let b = shared.read_column::<1>();
+    assert_eq!(b[3], 3.0 * 400.0);
+}
@@ -0,0 +1,290 @@
+#![cfg(feature = "alloc")]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use vec_soa::prelude::*;
+use vec_soa::{AllocError, Allocator, Global};
+
+#[derive(Default)]
+struct CountingAlloc {
+    allocs: Cell<usize>,
+    live: Cell<usize>,
+}
+
+unsafe impl Allocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        self.live.set(self.live.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+fn test_inline() {
+    let alloc = CountingAlloc::default();
+    let mut vec = SmallVecSoa3::<4, u32, String, u8, _>::new_in(&alloc);
+    assert_eq!(vec.capacity(), 4);
+    assert_eq!(vec.inline_capacity(), 4);
+
+    for i in 0..4 {
+        vec.push((i, i.to_string(), i as u8));
+    }
+
+    assert!(!vec.spilled());
+    assert_eq!(alloc.allocs.get(), 0);
+    assert_eq!(vec.len(), 4);
+
+    let slice = vec.as_slice();
+    let (a, b, c) = slice.soa_slices();
+    assert_eq!(a, &[0, 1, 2, 3]);
+    assert_eq!(b, &["0", "1", "2", "3"]);
+    assert_eq!(c, &[0, 1, 2, 3]);
+
+    vec.validate();
+}
+
+#[test]
+fn test_spill() {
+    let alloc = CountingAlloc::default();
+    let mut vec = SmallVecSoa2::<4, u64, String, _>::new_in(&alloc);
+
+    for i in 0..100 {
+        vec.push((i, i.to_string()));
+        vec.validate();
+    }
+
+    assert!(vec.spilled());
+    assert_eq!(alloc.live.get(), 2);
+
+    for (i, (a, b)) in vec.as_slice().iter().enumerate() {
+        assert_eq!(*a, i as u64);
+        assert_eq!(*b, i.to_string());
+    }
+
+    drop(vec);
+    assert_eq!(alloc.live.get(), 0);
+}
+
+#[test]
+fn test_unspill() {
+    let alloc = CountingAlloc::default();
+    let mut vec = SmallVecSoa2::<4, u32, String, _>::new_in(&alloc);
+
+    for i in 0..10 {
+        vec.push((i, i.to_string()));
+    }
+
+    while vec.len() > 3 {
+        vec.swap_remove(vec.len() - 1);
+    }
+
+    vec.shrink_to_fit();
+    assert!(!vec.spilled());
+    assert_eq!(vec.capacity(), 4);
+    assert_eq!(alloc.live.get(), 0);
+
+    let slice = vec.as_slice();
+    let (a, b) = slice.soa_slices();
+    assert_eq!(a, &[0, 1, 2]);
+    assert_eq!(b, &["0", "1", "2"]);
+
+    vec.validate();
+}
+
+#[test]
+fn test_shrink_spilled() {
+    let mut vec = SmallVecSoa2::<2, u32, u16>::with_capacity(50);
+    assert!(vec.spilled());
+    assert_eq!(vec.capacity(), 50);
+
+    for i in 0..10 {
+        vec.push((i, i as u16));
+    }
+
+    vec.shrink_to(12);
+    assert!(vec.spilled());
+    assert_eq!(vec.capacity(), 12);
+    assert_eq!(vec.as_slice().soa_slices().0, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    vec.validate();
+}
+
+#[test]
+fn test_moves() {
+    let mut vec = SmallVecSoa2::<8, u32, String>::new();
+    vec.push((1, "one".to_string()));
+    vec.push((2, "two".to_string()));
+
+    // Moving the vector moves its inline columns along with it.
+    let mut boxed = Box::new(vec);
+    assert_eq!(boxed.as_slice().soa_slices().1, &["one", "two"]);
+
+    boxed.push((3, "three".to_string()));
+
+    let mut vecs = vec![*boxed];
+    vecs.push(SmallVecSoa2::new());
+
+    let vec = &mut vecs[0];
+    *vec.as_mut_slice().index_mut(0).0 = 10;
+    assert_eq!(vec.as_slice().soa_slices().0, &[10, 2, 3]);
+    assert_eq!(
+        vec.as_slice().get(2).map(|(_, b)| b.as_str()),
+        Some("three")
+    );
+
+    vec.validate();
+}
+
+#[test]
+fn test_sync() {
+    fn assert_sync<T: Sync>(_: &T) {}
+
+    let mut vec = SmallVecSoa2::<2, u32, String>::new();
+    vec.push((1, "one".to_string()));
+    assert_sync(&vec);
+
+    // Both inline and spilled rows can be read from other threads.
+    for i in 2..5u32 {
+        std::thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(vec.as_slice().len(), i as usize - 1));
+        });
+        vec.push((i, i.to_string()));
+    }
+
+    assert!(vec.spilled());
+}
+
+#[test]
+fn test_drops() {
+    let rc = Rc::new(());
+
+    let mut vec = SmallVecSoa2::<3, Rc<()>, u8>::new();
+    for _ in 0..3 {
+        vec.push((rc.clone(), 0));
+    }
+    assert_eq!(Rc::strong_count(&rc), 4);
+
+    vec.swap_remove(0);
+    assert_eq!(Rc::strong_count(&rc), 3);
+
+    for _ in 0..5 {
+        vec.push((rc.clone(), 0));
+    }
+    assert!(vec.spilled());
+    assert_eq!(Rc::strong_count(&rc), 8);
+
+    vec.clear();
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    vec.push((rc.clone(), 0));
+    drop(vec);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn test_zero_inline() {
+    let alloc = CountingAlloc::default();
+    let mut vec = SmallVecSoa2::<0, u32, u8, _>::new_in(&alloc);
+    assert_eq!(vec.capacity(), 0);
+
+    vec.push((1, 1));
+    assert!(vec.spilled());
+    assert_eq!(alloc.live.get(), 2);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert!(!vec.spilled());
+    assert_eq!(alloc.live.get(), 0);
+}
+
+#[test]
+fn test_zst() {
+    let mut vec = SmallVecSoa2::<2, (), u32>::new();
+    for i in 0..10 {
+        vec.push(((), i));
+    }
+
+    assert_eq!(vec.as_slice().soa_slices().0.len(), 10);
+    vec.validate();
+}
+
+#[test]
+fn test_spill_growth() {
+    let mut vec = SmallVecSoa2::<4, u32, u8>::new().with_growth_policy(GrowthPolicy::Double);
+    for i in 0..5 {
+        vec.push((i, i as u8));
+    }
+
+    // The first spill grows from the inline capacity.
+    assert!(vec.spilled());
+    assert_eq!(vec.capacity(), 8);
+    assert_eq!(vec.as_slice().soa_slices().0, &[0, 1, 2, 3, 4]);
+    vec.validate();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_lock_capacity() {
+    let alloc = CountingAlloc::default();
+    let mut vec = SmallVecSoa2::<2, u32, String, _>::new_in(&alloc);
+    vec.lock_capacity();
+    assert!(vec.is_capacity_locked());
+
+    vec.push((1, "one".to_string()));
+    vec.push((2, "two".to_string()));
+    assert_eq!(
+        vec.try_push((3, "three".to_string())),
+        Err(TryReserveError::CapacityLocked)
+    );
+    assert!(!vec.spilled());
+    assert_eq!(alloc.allocs.get(), 0);
+
+    vec.unlock_capacity();
+    vec.push((3, "three".to_string()));
+    assert!(vec.spilled());
+    vec.validate();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "reallocation while the capacity is locked")]
+fn test_lock_capacity_unspill() {
+    let mut vec = SmallVecSoa2::<2, u32, u8>::with_capacity(8);
+    vec.push((1, 1));
+    vec.lock_capacity();
+    vec.shrink_to_fit();
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn test_alloc_stats() {
+    let mut vec = SmallVecSoa2::<2, u32, u8>::new();
+    for i in 0..3 {
+        vec.push((i, i as u8));
+    }
+
+    // Spilling allocates the heap columns from scratch.
+    assert_eq!(vec.alloc_stats().allocs, 2);
+
+    // Growing resizes the heap columns, which the allocator may have to copy.
+    let capacity = vec.capacity();
+    vec.reserve_exact(10);
+    let stats = vec.alloc_stats();
+    assert_eq!(stats.reallocs, 2);
+    assert!(stats.bytes_moved <= (capacity * 5) as u64);
+
+    // Moving back inline releases them again.
+    vec.swap_remove(0);
+    vec.shrink_to_fit();
+    assert!(!vec.spilled());
+    assert_eq!(vec.alloc_stats().deallocs, 2);
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "alloc")]
+use vec_soa::prelude::*;
+
+#[test]
+fn test_column_bytes() {
+    let mut vec = VecSoa3::<u8, u32, u64>::with_capacity(10);
+    assert_eq!(vec.per_column_bytes(), [10, 40, 80]);
+    assert_eq!(vec.heap_bytes(), 130);
+    assert_eq!(vec.wasted_bytes(), 130);
+
+    for i in 0..4 {
+        vec.push((i, i as u32, i as u64));
+    }
+
+    assert_eq!(vec.heap_bytes(), 130);
+    assert_eq!(vec.wasted_bytes(), 6 * 13);
+
+    vec.shrink_to_fit();
+    assert_eq!(vec.per_column_bytes(), [4, 16, 32]);
+    assert_eq!(vec.wasted_bytes(), 0);
+}
+
+#[test]
+fn test_column_bytes_zst() {
+    let vec = VecSoa2::<(), u16>::with_capacity(8);
+    assert_eq!(vec.per_column_bytes(), [0, 16]);
+    assert_eq!(vec.heap_bytes(), 16);
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn test_alloc_stats() {
+    let mut vec = VecSoa2::<u32, u64>::new();
+    assert_eq!(vec.alloc_stats(), AllocStats::default());
+
+    vec.push((0, 0));
+    assert_eq!(vec.alloc_stats().allocs, 2);
+    assert_eq!(vec.alloc_stats().reallocs, 0);
+
+    for i in 1..1000 {
+        vec.push((i, i as u64));
+    }
+
+    let stats = vec.alloc_stats();
+    assert_eq!(stats.allocs, 2);
+    assert!(stats.reallocs > 0);
+    assert_eq!(stats.reallocs % 2, 0);
+    assert_eq!(stats.deallocs, 0);
+
+    vec.reset_alloc_stats();
+    assert_eq!(vec.alloc_stats(), AllocStats::default());
+
+    // Each column is resized in place where the allocator can, and otherwise the
+    // allocator copies its whole old block.
+    let capacity = vec.capacity();
+    vec.try_reserve_exact(capacity).unwrap();
+    let stats = vec.alloc_stats();
+    assert_eq!(stats.allocs, 0);
+    assert_eq!(stats.reallocs, 2);
+    assert!(stats.bytes_moved <= (capacity * 12) as u64);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert_eq!(vec.alloc_stats().deallocs, 2);
+}
+
+#[test]
+#[cfg(feature = "stats")]
+fn test_alloc_stats_zst() {
+    let mut vec = VecSoa2::<(), u8>::with_capacity(4);
+    assert_eq!(vec.alloc_stats().allocs, 1);
+
+    vec.reserve(100);
+    assert_eq!(vec.alloc_stats().reallocs, 1);
+}
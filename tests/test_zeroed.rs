@@ -0,0 +1,160 @@
+#![cfg(feature = "alloc")]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::num::NonZeroU32;
+use std::ptr::NonNull;
+
+use vec_soa::prelude::*;
+use vec_soa::{AllocError, Allocator, Global};
+
+#[derive(Default)]
+struct ZeroedAlloc {
+    zeroed: Cell<usize>,
+}
+
+unsafe impl Allocator for ZeroedAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.zeroed.set(self.zeroed.get() + 1);
+        Global.allocate_zeroed(layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.zeroed.set(self.zeroed.get() + 1);
+        unsafe { Global.grow_zeroed(ptr, old_layout, new_layout) }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+#[test]
+fn test_with_len_zeroed() {
+    let vec = VecSoa3::<u32, f64, Option<NonZeroU32>>::with_len_zeroed(100);
+    assert_eq!(vec.len(), 100);
+    assert_eq!(vec.capacity(), 100);
+
+    let (a, b, c) = vec.soa_slices();
+    assert!(a.iter().all(|&x| x == 0));
+    assert!(b.iter().all(|&x| x == 0.0));
+    assert!(c.iter().all(|x| x.is_none()));
+
+    vec.validate();
+}
+
+#[test]
+fn test_with_len_zeroed_in() {
+    let alloc = ZeroedAlloc::default();
+    let vec = VecSoa2::<u8, u64, _>::with_len_zeroed_in(16, &alloc);
+    assert_eq!(alloc.zeroed.get(), 2);
+    assert_eq!(vec.len(), 16);
+    vec.validate();
+}
+
+#[test]
+fn test_resize_zeroed_grow() {
+    let alloc = ZeroedAlloc::default();
+    let mut vec = VecSoa2::<u32, u16, _>::new_in(&alloc);
+
+    vec.resize_zeroed(10);
+    assert_eq!(alloc.zeroed.get(), 2);
+    assert_eq!(vec.len(), 10);
+
+    for (a, b) in vec.iter_mut() {
+        *a = 7;
+        *b = 7;
+    }
+
+    vec.resize_zeroed(1000);
+    assert_eq!(alloc.zeroed.get(), 4);
+
+    let (a, b) = vec.soa_slices();
+    assert!(a[..10].iter().all(|&x| x == 7));
+    assert!(b[..10].iter().all(|&x| x == 7));
+    assert!(a[10..].iter().all(|&x| x == 0));
+    assert!(b[10..].iter().all(|&x| x == 0));
+
+    vec.validate();
+}
+
+#[test]
+fn test_resize_zeroed_stale() {
+    let mut vec = VecSoa2::<u32, u64>::with_capacity(8);
+    for i in 1..=8 {
+        vec.push((i, i as u64));
+    }
+
+    // Leave stale values in the spare capacity.
+    for _ in 0..5 {
+        vec.swap_remove(0);
+    }
+
+    vec.resize_zeroed(8);
+    assert_eq!(vec.capacity(), 8);
+
+    let (a, b) = vec.soa_slices();
+    assert!(a[3..].iter().all(|&x| x == 0));
+    assert!(b[3..].iter().all(|&x| x == 0));
+
+    // Stale values in the old spare capacity must be zeroed when growing too.
+    for _ in 0..5 {
+        vec.swap_remove(0);
+    }
+
+    vec.resize_zeroed(20);
+
+    let (a, b) = vec.soa_slices();
+    assert!(a[3..].iter().all(|&x| x == 0));
+    assert!(b[3..].iter().all(|&x| x == 0));
+
+    vec.validate();
+}
+
+thread_local! {
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Tracked(#[allow(dead_code)] u32);
+
+unsafe impl Zeroable for Tracked {}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPS.with(|d| d.set(d.get() + 1));
+    }
+}
+
+#[test]
+fn test_resize_zeroed_shrink() {
+    let mut vec = VecSoa2::<Tracked, u8>::with_len_zeroed(10);
+
+    vec.resize_zeroed(4);
+    assert_eq!(vec.len(), 4);
+    assert_eq!(DROPS.with(Cell::get), 6);
+
+    vec.resize_zeroed(4);
+    assert_eq!(DROPS.with(Cell::get), 6);
+
+    drop(vec);
+    assert_eq!(DROPS.with(Cell::get), 10);
+}
+
+#[test]
+fn test_zeroed_zst() {
+    let mut vec = VecSoa2::<(), u32>::with_len_zeroed(5);
+    vec.resize_zeroed(50);
+    assert_eq!(vec.len(), 50);
+    assert_eq!(vec.soa_slices().0.len(), 50);
+    vec.validate();
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "alloc")]
 use vec_soa::prelude::*;
 
 #[test]
@@ -180,3 +181,19 @@ pub fn test_iter_zst() {
 
     assert_eq!(sum, 10);
 }
+
+#[test]
+pub fn test_shrink() {
+    let mut vec = VecSoa3::<u16, u32, ()>::with_capacity(16);
+
+    vec.push((1, 2, ()));
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 1);
+
+    vec.clear();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 0);
+
+    vec.push((4, 5, ()));
+    assert_eq!(vec.len(), 1);
+}